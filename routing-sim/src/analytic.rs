@@ -0,0 +1,209 @@
+//! 固定経路方式の呼損率の解析的な近似（Erlang固定点近似）
+//!
+//! 各リンクの呼損が互いに独立であると仮定し、リンク`l`に加わる呼量を
+//! 経路上の他のリンクで呼損にならなかった分だけに減らして（reduced load）、
+//! リンクの呼損率をErlang-B式（クラスが複数ならKaufman-Roberts法）で求める。
+//! リンクの呼損率が収束するまでこれを繰り返す。
+//!
+//! 呼はポアソン到着と仮定する。保持時間の分布にはよらないが、
+//! 等間隔到着のシミュレーションとは到着過程の違いの分だけずれる。
+
+use crate::router::Router;
+use crate::topology::Topology;
+use crate::traffic::{TrafficClasses, TrafficMatrix};
+
+/// 固定点の反復を打ち切るリンク呼損率の変化量
+const TOLERANCE: f64 = 1e-10;
+/// 固定点の反復の上限回数
+const MAX_ITERATIONS: usize = 10000;
+
+/// Erlang-B式：呼量`load`（アーラン）が`servers`回線に加わったときの呼損率
+pub fn erlang_b(load: f64, servers: usize) -> f64 {
+    // B(0) = 1, B(k) = a B(k-1) / (k + a B(k-1))
+    let mut blocking = 1.0;
+    for k in 1..=servers {
+        blocking = load * blocking / (k as f64 + load * blocking);
+    }
+    blocking
+}
+
+/// Kaufman-Roberts法：帯域`demand`の呼が呼量`load`で加わるクラスの組
+/// `classes`について、容量`capacity`のリンクでのクラスごとの呼損率を求める
+pub fn kaufman_roberts(capacity: usize, classes: &[(usize, f64)]) -> Vec<f64> {
+    // q[j]は使用中の帯域がjである確率（正規化前）
+    let mut q = vec![0.0; capacity + 1];
+    q[0] = 1.0;
+    for j in 1..=capacity {
+        q[j] = classes
+            .iter()
+            .filter(|&&(demand, _)| demand <= j)
+            .map(|&(demand, load)| demand as f64 * load * q[j - demand])
+            .sum::<f64>()
+            / j as f64;
+        // 桁あふれしないように途中で縮める
+        if q[j] > 1e200 {
+            for value in &mut q[..=j] {
+                *value /= 1e200;
+            }
+        }
+    }
+    let total: f64 = q.iter().sum();
+
+    classes
+        .iter()
+        .map(|&(demand, _)| {
+            if demand > capacity {
+                return 1.0;
+            }
+            q[capacity + 1 - demand..].iter().sum::<f64>() / total
+        })
+        .collect()
+}
+
+/// ノード対ごとの固定経路と呼量
+#[derive(Clone, Debug, PartialEq)]
+pub struct Route {
+    /// 経路上のリンク（経路が無ければ`None`）
+    pub links: Option<Vec<usize>>,
+    /// このノード対に加わる呼量（アーラン、全クラスの合計）
+    pub load: f64,
+}
+
+/// 固定経路方式`router`の全ノード対の経路と呼量を求める
+///
+/// 呼量は、全体の呼量`total_load`（到着率×平均保持時間、またはトラヒック行列が
+/// 与える呼量の合計）をトラヒック行列の値の比で各ノード対に分けたもの。
+/// `router`は空き容量によらず経路が決まる方式であること。
+pub fn routes(
+    topology: &Topology,
+    router: &mut dyn Router,
+    traffic: &TrafficMatrix,
+    total_load: f64,
+) -> Vec<Route> {
+    let node_num = topology.node_num();
+    let capacities = topology.capacities();
+    let traffic_total = traffic.total_weight();
+    let mut routes = Vec::new();
+    for source in 0..node_num {
+        for destination in 0..node_num {
+            let share = traffic.weight(source, destination);
+            if source == destination || share <= 0.0 {
+                continue;
+            }
+            let links = router
+                .find_path(source, destination, &capacities, 1)
+                .and_then(|path| topology.path_links(&path));
+            routes.push(Route {
+                links,
+                load: total_load * share / traffic_total,
+            });
+        }
+    }
+    routes
+}
+
+/// Erlang固定点近似の結果
+#[derive(Clone, Debug, PartialEq)]
+pub struct Estimate {
+    /// リンクごと・クラスごとの呼損率（`[リンク][クラス]`）
+    pub link_blocking: Vec<Vec<f64>>,
+    /// クラスごとの呼損率
+    pub class_blocking: Vec<f64>,
+    /// 全体の呼損率（発生する呼の数による加重平均）
+    pub blocking: f64,
+    /// 反復回数
+    pub iterations: usize,
+    /// 反復が収束したか
+    pub converged: bool,
+}
+
+/// Erlang固定点近似で呼損率を求める
+pub fn reduced_load(topology: &Topology, routes: &[Route], classes: &TrafficClasses) -> Estimate {
+    let link_num = topology.links().len();
+    let class_num = classes.len();
+    let share_total: f64 = classes.classes().iter().map(|class| class.share).sum();
+    let class_share: Vec<f64> = classes
+        .classes()
+        .iter()
+        .map(|class| class.share / share_total)
+        .collect();
+
+    let mut link_blocking = vec![vec![0.0; class_num]; link_num];
+    let mut iterations = 0;
+    let mut converged = false;
+
+    while iterations < MAX_ITERATIONS {
+        iterations += 1;
+        // 他のリンクで呼損にならなかった呼量をリンクごとに集める
+        let mut offered = vec![vec![0.0; class_num]; link_num];
+        for route in routes {
+            let Some(links) = &route.links else {
+                continue;
+            };
+            for (class, share) in class_share.iter().enumerate() {
+                let load = route.load * share;
+                for &link in links {
+                    let thinned = links
+                        .iter()
+                        .filter(|&&other| other != link)
+                        .map(|&other| 1.0 - link_blocking[other][class])
+                        .product::<f64>();
+                    offered[link][class] += load * thinned;
+                }
+            }
+        }
+
+        let mut change: f64 = 0.0;
+        for (link, blocking) in link_blocking.iter_mut().enumerate() {
+            let capacity = topology.links()[link].capacity.max(0) as usize;
+            let updated = if class_num == 1 {
+                // 帯域dの呼だけなら、容量C / d回線のErlang-B式と同じ
+                let servers = capacity / classes.demand(0) as usize;
+                vec![erlang_b(offered[link][0], servers)]
+            } else {
+                let loads: Vec<(usize, f64)> = (0..class_num)
+                    .map(|class| (classes.demand(class) as usize, offered[link][class]))
+                    .collect();
+                kaufman_roberts(capacity, &loads)
+            };
+            for (old, new) in blocking.iter_mut().zip(updated) {
+                change = change.max((*old - new).abs());
+                *old = new;
+            }
+        }
+        if change < TOLERANCE {
+            converged = true;
+            break;
+        }
+    }
+
+    // 経路の呼損率 = 1 - Π(1 - リンクの呼損率)
+    let mut attempts = vec![0.0; class_num];
+    let mut blocked = vec![0.0; class_num];
+    for route in routes {
+        for (class, share) in class_share.iter().enumerate() {
+            let load = route.load * share;
+            let loss = match &route.links {
+                Some(links) => {
+                    1.0 - links
+                        .iter()
+                        .map(|&link| 1.0 - link_blocking[link][class])
+                        .product::<f64>()
+                }
+                None => 1.0,
+            };
+            attempts[class] += load;
+            blocked[class] += load * loss;
+        }
+    }
+
+    Estimate {
+        class_blocking: (0..class_num)
+            .map(|class| blocked[class] / attempts[class])
+            .collect(),
+        blocking: blocked.iter().sum::<f64>() / attempts.iter().sum::<f64>(),
+        link_blocking,
+        iterations,
+        converged,
+    }
+}
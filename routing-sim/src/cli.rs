@@ -0,0 +1,1010 @@
+//! 各バイナリ共通のコマンドライン
+//!
+//! ```text
+//! shortest-path [--topology PATH] [--strategy NAME] <simulate|sweep|compare|estimate|path> ...
+//! ```
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use crate::analytic;
+use crate::fairness::PairStats;
+use crate::output::{Column, ColumnType, Format, Metadata, ResultWriter, Value};
+use crate::process::{ArrivalProcess, HoldingTime};
+use crate::reservation::{LinkThreshold, TrunkReservation};
+use crate::rng;
+use crate::router::{self, QueueKind, Router, WidthMode};
+use crate::routes::RouteStats;
+use crate::simulator::{RunResult, Simulator, Warmup};
+use crate::stats::{self, ConfidenceInterval, PairedDifference, StoppingRule};
+use crate::topology::Topology;
+use crate::traffic::{TrafficClasses, TrafficMatrix, TrafficModel};
+use clap::builder::PossibleValuesParser;
+use clap::{Args, Parser, Subcommand};
+use rayon::prelude::*;
+
+#[derive(Parser, Debug)]
+#[command(version, about = "経路制御方式の呼損率シミュレーション")]
+struct Cli {
+    /// トポロジファイル（`接続元ノード 接続先ノード 距離 リンク容量`）
+    #[arg(long, short, global = true, default_value = "../distance.txt")]
+    topology: PathBuf,
+
+    /// 経路制御方式（省略時はバイナリ名の方式）
+    #[arg(long, short, global = true, value_parser = PossibleValuesParser::new(router::STRATEGIES))]
+    strategy: Option<String>,
+
+    /// k-shortest-path・least-loaded・dynamic-alternativeの候補経路の数
+    #[arg(long, global = true, default_value_t = 3, value_parser = positive)]
+    k: usize,
+
+    /// maximum-shortest-path(-on-demand)の経路の選び方
+    /// （widest-shortest, shortest-widest, widest）
+    #[arg(long, global = true, default_value = "shortest-widest")]
+    width_mode: WidthMode,
+
+    /// shortest-path-on-demandの探索に使う優先度付きキュー（heap, bucket）
+    #[arg(long, global = true, default_value = "heap")]
+    queue: QueueKind,
+
+    /// 並列実行に使うスレッド数（省略時はCPUのコア数）
+    #[arg(long, global = true, value_parser = positive)]
+    threads: Option<usize>,
+
+    /// 乱数のマスターシード（省略時はランダムに決めて表示する）
+    #[arg(long, global = true)]
+    seed: Option<u64>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// 1つの平均保持時間について呼損率を評価する
+    Simulate {
+        /// 平均保持時間（タイムユニット）
+        #[arg(short, default_value_t = 1)]
+        n: usize,
+
+        /// リンクごとの使用率とボトルネックになった回数の表を書き出すファイル
+        #[arg(long)]
+        link_stats: Option<PathBuf>,
+
+        /// ノード対ごとの呼損率の表を書き出すファイル（公平性の集計も表示する）
+        #[arg(long)]
+        pair_stats: Option<PathBuf>,
+
+        /// 経路のホップ数・距離・伸長率の度数分布を書き出すファイル（集計も表示する）
+        #[arg(long)]
+        route_stats: Option<PathBuf>,
+
+        /// 表の出力形式（csv, jsonl, columnar）
+        #[arg(long, default_value = "csv")]
+        format: Format,
+
+        #[command(flatten)]
+        run: RunArgs,
+    },
+    /// 平均保持時間を変えながら呼損率を評価し、結果ファイルに書き出す
+    Sweep {
+        /// 保持時間の開始値
+        #[arg(long, default_value_t = 1)]
+        from: usize,
+
+        /// 保持時間の終了値（この値を含む）
+        #[arg(long, default_value_t = 10000)]
+        to: usize,
+
+        /// 保持時間の刻み
+        #[arg(long, default_value_t = 1, value_parser = positive)]
+        step: usize,
+
+        /// 出力するファイル（省略時は`results.<拡張子>`）。既にあれば番号を付けた名前で新しく作る
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+
+        /// 出力形式（csv, jsonl, columnar）
+        #[arg(long, default_value = "csv")]
+        format: Format,
+
+        #[command(flatten)]
+        run: RunArgs,
+    },
+    /// 同じ呼の列を複数の方式に流し、呼損率の差を比較する
+    Compare {
+        /// 比較する方式（カンマ区切り、先頭が基準）
+        #[arg(
+            long,
+            required = true,
+            value_delimiter = ',',
+            value_parser = PossibleValuesParser::new(router::STRATEGIES)
+        )]
+        strategies: Vec<String>,
+
+        /// 平均保持時間（タイムユニット）
+        #[arg(short, default_value_t = 1)]
+        n: usize,
+
+        /// 方式ごとのノード対ごとの呼損率の表を書き出すファイル（公平性の集計も表示する）
+        #[arg(long)]
+        pair_stats: Option<PathBuf>,
+
+        /// 方式ごとの経路のホップ数・距離・伸長率の度数分布を書き出すファイル（集計も表示する）
+        #[arg(long)]
+        route_stats: Option<PathBuf>,
+
+        /// 表の出力形式（csv, jsonl, columnar）
+        #[arg(long, default_value = "csv")]
+        format: Format,
+
+        #[command(flatten)]
+        run: RunArgs,
+    },
+    /// 固定経路方式の呼損率をErlang固定点近似で見積もる
+    Estimate {
+        /// 平均保持時間（タイムユニット）
+        #[arg(short, default_value_t = 1)]
+        n: usize,
+
+        /// シミュレーションも実行して見積もりと並べて表示する
+        #[arg(long)]
+        simulate: bool,
+
+        #[command(flatten)]
+        run: RunArgs,
+    },
+    /// 空きのあるネットワークで選ばれる経路を表示する
+    Path {
+        /// 始点ノード
+        source: usize,
+        /// 終点ノード
+        destination: usize,
+
+        /// 呼の帯域
+        #[arg(long, default_value_t = 1, value_parser = positive)]
+        demand: usize,
+    },
+}
+
+#[derive(Args, Debug)]
+struct RunArgs {
+    /// 1点あたりのシミュレーション実行回数（--precision指定時は最少回数）
+    #[arg(long, default_value_t = 10, value_parser = positive)]
+    runs: usize,
+
+    /// 信頼区間の信頼水準
+    #[arg(long, default_value_t = 0.95, value_parser = probability)]
+    confidence: f64,
+
+    /// 信頼区間の相対半幅（半幅 / 平均）がこの値以下になるまで実行を追加する
+    /// （compareでは全方式の呼損率がこの値以下になるまで）
+    #[arg(long, value_parser = positive_rate)]
+    precision: Option<f64>,
+
+    /// --precision指定時の最多実行回数
+    #[arg(long, default_value_t = 1000, value_parser = positive)]
+    max_runs: usize,
+
+    /// 1回の実行で発生させる呼の数
+    #[arg(long, default_value_t = 10000, value_parser = positive)]
+    attempts: usize,
+
+    /// 迂回経路の呼に対して各リンクに残す予約容量（全リンク共通）
+    #[arg(long)]
+    reservation: Option<usize>,
+
+    /// リンクごとの予約容量（`ノード1-ノード2=予約量`のカンマ区切り）。--reservationより優先
+    #[arg(long, value_delimiter = ',')]
+    link_reservation: Vec<LinkThreshold>,
+
+    /// 集計から除くウォームアップ期間（none、先頭の呼の数、mser5）
+    #[arg(long, default_value = "none")]
+    warmup: Warmup,
+
+    /// 呼の到着過程（poisson, deterministic, mmpp:λ0,λ1,r0,r1）
+    #[arg(long, default_value = "deterministic")]
+    arrival: ArrivalProcess,
+
+    /// poisson・deterministicの到着率（--traffic-matrix指定時は行列の呼量から決まる）
+    #[arg(long, default_value_t = 1.0, value_parser = positive_rate, conflicts_with = "traffic_matrix")]
+    arrival_rate: f64,
+
+    /// 保持時間分布（exponential, deterministic, lognormal:σ）。平均は保持時間n
+    #[arg(long, default_value = "deterministic")]
+    holding: HoldingTime,
+
+    /// ノード対ごとの呼量の重み（uniform, gravity, hotspot:ノード,倍率）
+    #[arg(long, default_value = "uniform")]
+    traffic: TrafficModel,
+
+    /// トラヒック行列ファイル（`始点ノード 終点ノード 呼量`）。--trafficの代わりに使う。
+    /// 呼量はアーランで、ノード対の到着率は呼量 / 平均保持時間nになる
+    #[arg(long, conflicts_with = "traffic")]
+    traffic_matrix: Option<PathBuf>,
+
+    /// 呼のクラス（`帯域:割合`のカンマ区切り、例: 1:0.7,2:0.2,6:0.1）
+    #[arg(long, default_value = "1")]
+    classes: TrafficClasses,
+}
+
+impl RunArgs {
+    fn simulator<'a>(&self, topology: &'a Topology) -> Result<Simulator<'a>, String> {
+        if let Warmup::Calls(calls) = self.warmup {
+            if calls >= self.attempts {
+                return Err(format!(
+                    "warm-up ({} calls) must be shorter than --attempts ({})",
+                    calls, self.attempts
+                ));
+            }
+        }
+        let mut simulator = Simulator::new(topology, self.attempts)
+            .with_arrival(self.arrival.with_rate(self.arrival_rate))
+            .with_holding(self.holding)
+            .with_traffic(self.traffic_matrix(topology)?)
+            .with_classes(self.classes.clone())
+            .with_warmup(self.warmup);
+        if let Some(reservation) = self.trunk_reservation(topology)? {
+            simulator = simulator.with_reservation(reservation);
+        }
+        Ok(simulator)
+    }
+
+    fn trunk_reservation(&self, topology: &Topology) -> Result<Option<TrunkReservation>, String> {
+        if self.reservation.is_none() && self.link_reservation.is_empty() {
+            return Ok(None);
+        }
+        let mut reservation =
+            TrunkReservation::new(topology, self.reservation.unwrap_or(0) as isize);
+        for threshold in &self.link_reservation {
+            let link = topology
+                .link_between(threshold.node1, threshold.node2)
+                .ok_or_else(|| {
+                    format!(
+                        "no link between node{} and node{}",
+                        threshold.node1, threshold.node2
+                    )
+                })?;
+            reservation.set_threshold(link, threshold.threshold);
+        }
+        Ok(Some(reservation))
+    }
+
+    /// 結果ファイルに記録する実行回数の指定
+    fn replications_description(&self) -> String {
+        match self.precision {
+            Some(precision) => format!(
+                "adaptive(min={},max={},precision={})",
+                self.runs,
+                self.max_runs.max(self.runs),
+                precision
+            ),
+            None => self.runs.to_string(),
+        }
+    }
+
+    /// 結果ファイルに記録するパラメータ
+    fn parameters(&self, options: &router::Options) -> Vec<(String, String)> {
+        [
+            ("attempts", self.attempts.to_string()),
+            ("arrival", self.arrival_description()),
+            ("holding", self.holding.to_string()),
+            ("traffic", self.traffic_description()),
+            ("classes", self.classes.to_string()),
+            ("reservation", self.reservation_description()),
+            ("warmup", self.warmup.to_string()),
+            ("confidence", self.confidence.to_string()),
+            ("k", options.k.to_string()),
+            ("width_mode", options.width.to_string()),
+            ("queue", options.queue.to_string()),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect()
+    }
+
+    /// 結果ファイルに記録する到着過程
+    fn arrival_description(&self) -> String {
+        match (&self.traffic_matrix, self.arrival) {
+            (None, arrival) => arrival.with_rate(self.arrival_rate).to_string(),
+            (Some(_), ArrivalProcess::Poisson { .. }) => "poisson(rate=load/n)".to_string(),
+            (Some(_), ArrivalProcess::Deterministic { .. }) => {
+                "deterministic(rate=load/n)".to_string()
+            }
+            (Some(_), arrival) => format!("{}(scaled to rate=load/n)", arrival),
+        }
+    }
+
+    /// 結果ファイルに記録するトランク予約の指定
+    fn reservation_description(&self) -> String {
+        let mut parts = vec![self.reservation.unwrap_or(0).to_string()];
+        parts.extend(self.link_reservation.iter().map(|t| t.to_string()));
+        parts.join(",")
+    }
+
+    fn traffic_matrix(&self, topology: &Topology) -> Result<TrafficMatrix, String> {
+        let traffic = match &self.traffic_matrix {
+            Some(path) => TrafficMatrix::load(path, topology.node_num())
+                .map_err(|e| format!("{}: {}", path.display(), e))?,
+            None => {
+                if let TrafficModel::HotSpot { node, .. } = self.traffic {
+                    if node >= topology.node_num() {
+                        return Err(format!(
+                            "hot-spot node must be less than {}",
+                            topology.node_num()
+                        ));
+                    }
+                }
+                self.traffic.build(topology)
+            }
+        };
+        Ok(traffic)
+    }
+
+    /// --precisionが指定されていれば実行回数を決める停止規則
+    fn stopping_rule(&self) -> Option<StoppingRule> {
+        self.precision.map(|precision| StoppingRule {
+            level: self.confidence,
+            relative_half_width: precision,
+            min_runs: self.runs,
+            max_runs: self.max_runs.max(self.runs),
+        })
+    }
+
+    /// 結果ファイルに記録するトラヒックの指定
+    fn traffic_description(&self) -> String {
+        match &self.traffic_matrix {
+            Some(path) => format!("file:{}", path.display()),
+            None => self.traffic.to_string(),
+        }
+    }
+}
+
+/// コマンドラインを解釈して実行する。`default_strategy`は`--strategy`省略時の方式
+pub fn main(default_strategy: &str) {
+    let cli = Cli::parse();
+    if let Err(message) = run(cli, default_strategy) {
+        eprintln!("Error: {}", message);
+        process::exit(1);
+    }
+}
+
+fn run(cli: Cli, default_strategy: &str) -> Result<(), String> {
+    let topology =
+        Topology::load(&cli.topology).map_err(|e| format!("{}: {}", cli.topology.display(), e))?;
+    let strategy = cli.strategy.as_deref().unwrap_or(default_strategy);
+    let options = router::Options {
+        k: cli.k,
+        width: cli.width_mode,
+        queue: cli.queue,
+    };
+    let mut router = router::build_with(strategy, &topology, &options)
+        .ok_or_else(|| format!("unknown strategy: {}", strategy))?;
+    let seed = cli.seed.unwrap_or_else(rng::random_seed);
+    if let Some(threads) = cli.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .map_err(|e| e.to_string())?;
+    }
+
+    // 結果ファイルに記録する実験の条件
+    let metadata = |run: &RunArgs| Metadata {
+        strategy: strategy.to_string(),
+        topology: cli.topology.display().to_string(),
+        topology_hash: topology.fingerprint(),
+        seed,
+        replications: run.replications_description(),
+        parameters: run.parameters(&options),
+    };
+
+    match cli.command {
+        Command::Simulate {
+            n,
+            link_stats,
+            pair_stats,
+            route_stats,
+            format,
+            run,
+        } => {
+            let simulator = run.simulator(&topology)?;
+            println!("Seed: {}", seed);
+            let results = replicate(&simulator, router.as_ref(), n, &run, seed);
+            print_runs(&results);
+            print_summary(n, &run, &results);
+            if let Some(path) = link_stats {
+                let mut metadata = metadata(&run);
+                metadata.parameters.push(("n".to_string(), n.to_string()));
+                let path = write_link_stats(&path, format, metadata, &topology, &results)?;
+                println!("Link statistics written to {}", path.display());
+            }
+            if let Some(path) = pair_stats {
+                let stats = [PairStats::new(&topology, &results)];
+                println!();
+                print_fairness(&[strategy], &stats);
+                let mut metadata = metadata(&run);
+                metadata.parameters.push(("n".to_string(), n.to_string()));
+                let path = write_pair_stats(&path, format, metadata, &[strategy], &stats)?;
+                println!("Pair statistics written to {}", path.display());
+            }
+            if let Some(path) = route_stats {
+                let stats = [RouteStats::new(&topology, &results)];
+                println!();
+                print_routes(&[strategy], &stats);
+                let mut metadata = metadata(&run);
+                metadata.parameters.push(("n".to_string(), n.to_string()));
+                let path = write_route_stats(&path, format, metadata, &[strategy], &stats)?;
+                println!("Route statistics written to {}", path.display());
+            }
+        }
+        Command::Compare {
+            strategies,
+            n,
+            pair_stats,
+            route_stats,
+            format,
+            run,
+        } => {
+            if strategies.len() < 2 {
+                return Err("compare needs at least two strategies".to_string());
+            }
+            let mut routers = Vec::with_capacity(strategies.len());
+            for name in &strategies {
+                routers.push(
+                    router::build_with(name, &topology, &options)
+                        .ok_or_else(|| format!("unknown strategy: {}", name))?,
+                );
+            }
+            let simulator = run.simulator(&topology)?;
+            println!("Seed: {}", seed);
+            let results = match run.stopping_rule() {
+                Some(rule) => simulator.compare_until(&routers, n, seed, &rule),
+                None => simulator.compare(&routers, n, run.runs, seed),
+            };
+            let loss = |run: &Vec<RunResult>, i: usize| run[i].call_loss_rate();
+
+            println!("{:<34} {:>12}", "strategy", "call_loss");
+            for (i, name) in strategies.iter().enumerate() {
+                let values: Vec<f64> = results.iter().map(|run| loss(run, i)).collect();
+                println!("{:<34} {:>12.6}", name, stats::mean(&values));
+            }
+            if run.classes.len() > 1 {
+                println!("\nCall loss rate per class:");
+                print!("{:<34}", "strategy");
+                for class in run.classes.classes() {
+                    print!(" {:>12}", format!("demand={}", class.demand));
+                }
+                println!();
+                for (i, name) in strategies.iter().enumerate() {
+                    let results: Vec<RunResult> =
+                        results.iter().map(|run| run[i].clone()).collect();
+                    print!("{:<34}", name);
+                    for class in 0..run.classes.len() {
+                        print!(" {:>12}", or_dash(class_average(&results, class), 6));
+                    }
+                    println!();
+                }
+            }
+            println!(
+                "\nPaired difference from {} (same call arrivals, {} runs):",
+                strategies[0],
+                results.len()
+            );
+            println!(
+                "{:<34} {:>12} {:>12} {:>12}",
+                "strategy", "mean", "variance", "std_error"
+            );
+            for (i, name) in strategies.iter().enumerate().skip(1) {
+                let difference =
+                    PairedDifference::new(results.iter().map(|run| (loss(run, i), loss(run, 0))));
+                println!(
+                    "{:<34} {:>12.6} {:>12.3e} {:>12.3e}",
+                    name, difference.mean, difference.variance, difference.std_error
+                );
+            }
+
+            let names: Vec<&str> = strategies.iter().map(String::as_str).collect();
+            if let Some(path) = pair_stats {
+                let stats: Vec<PairStats> = (0..strategies.len())
+                    .map(|i| {
+                        let results: Vec<RunResult> =
+                            results.iter().map(|run| run[i].clone()).collect();
+                        PairStats::new(&topology, &results)
+                    })
+                    .collect();
+                println!();
+                print_fairness(&names, &stats);
+                let mut metadata = metadata(&run);
+                metadata.strategy = strategies.join(",");
+                metadata.parameters.push(("n".to_string(), n.to_string()));
+                let path = write_pair_stats(&path, format, metadata, &names, &stats)?;
+                println!("Pair statistics written to {}", path.display());
+            }
+
+            if let Some(path) = route_stats {
+                let stats: Vec<RouteStats> = (0..strategies.len())
+                    .map(|i| {
+                        let results: Vec<RunResult> =
+                            results.iter().map(|run| run[i].clone()).collect();
+                        RouteStats::new(&topology, &results)
+                    })
+                    .collect();
+                println!();
+                print_routes(&names, &stats);
+                let mut metadata = metadata(&run);
+                metadata.strategy = strategies.join(",");
+                metadata.parameters.push(("n".to_string(), n.to_string()));
+                let path = write_route_stats(&path, format, metadata, &names, &stats)?;
+                println!("Route statistics written to {}", path.display());
+            }
+        }
+        Command::Sweep {
+            from,
+            to,
+            step,
+            output,
+            format,
+            run,
+        } => {
+            let simulator = run.simulator(&topology)?;
+            let output = output.unwrap_or_else(|| format!("results.{}", format.extension()).into());
+            let columns = vec![
+                Column::new("n", ColumnType::Integer),
+                Column::new("average_call_loss_rate", ColumnType::Float),
+                Column::new("std_dev", ColumnType::Float),
+                Column::new("ci_lower", ColumnType::Float),
+                Column::new("ci_upper", ColumnType::Float),
+                Column::new("runs", ColumnType::Integer),
+            ];
+            let mut writer = ResultWriter::create(&output, format, metadata(&run), columns)
+                .map_err(|e| format!("{}: {}", output.display(), e))?;
+            println!("Writing results to {}", writer.path().display());
+            println!("Seed: {}", seed);
+
+            // 評価点をスレッド数ずつ並列に実行し、終わった分から順に書き出す
+            let points: Vec<usize> = (from..=to).step_by(step).collect();
+            for chunk in points.chunks(rayon::current_num_threads()) {
+                let chunk_results: Vec<Vec<RunResult>> = chunk
+                    .par_iter()
+                    .map(|&n| replicate(&simulator, router.as_ref(), n, &run, seed))
+                    .collect();
+                for (&n, results) in chunk.iter().zip(chunk_results) {
+                    println!("\nSimulating for n = {}", n);
+                    print_runs(&results);
+                    let interval = print_summary(n, &run, &results);
+                    // nと平均呼損率、その信頼区間を書き出す
+                    writer
+                        .write_row(vec![
+                            n.into(),
+                            interval.mean.into(),
+                            interval.std_dev.into(),
+                            interval.lower().into(),
+                            interval.upper().into(),
+                            results.len().into(),
+                        ])
+                        .map_err(|e| format!("{}: {}", writer.path().display(), e))?;
+                }
+            }
+            writer
+                .finish()
+                .map_err(|e| format!("{}: {}", output.display(), e))?;
+        }
+        Command::Estimate { n, simulate, run } => {
+            if !router.is_fixed() {
+                return Err(format!(
+                    "{} is not a fixed routing strategy; estimate supports shortest-path and maximum-shortest-path",
+                    strategy
+                ));
+            }
+            let traffic = run.traffic_matrix(&topology)?;
+            let total_load = traffic
+                .offered_load()
+                .unwrap_or(run.arrival.with_rate(run.arrival_rate).mean_rate() * n as f64);
+            let routes = analytic::routes(&topology, router.as_mut(), &traffic, total_load);
+            let estimate = analytic::reduced_load(&topology, &routes, &run.classes);
+            if !estimate.converged {
+                eprintln!(
+                    "Warning: fixed point did not converge after {} iterations",
+                    estimate.iterations
+                );
+            }
+            println!("Offered load: {} Erlang", total_load);
+            println!(
+                "Estimated call loss rate for n = {}: {}",
+                n, estimate.blocking
+            );
+            if run.classes.len() > 1 {
+                for (i, class) in run.classes.classes().iter().enumerate() {
+                    println!(
+                        "  class {} (demand = {}): {}",
+                        i, class.demand, estimate.class_blocking[i]
+                    );
+                }
+            }
+
+            if simulate {
+                println!("\nSeed: {}", seed);
+                let simulator = run.simulator(&topology)?;
+                let results = replicate(&simulator, router.as_ref(), n, &run, seed);
+                print_runs(&results);
+                print_summary(n, &run, &results);
+            }
+        }
+        Command::Path {
+            source,
+            destination,
+            demand,
+        } => {
+            let node_num = topology.node_num();
+            if source >= node_num || destination >= node_num {
+                return Err(format!("node number must be less than {}", node_num));
+            }
+            match router.find_path(source, destination, &topology.capacities(), demand as isize) {
+                Some(path) => {
+                    let nodes: Vec<String> = path.iter().map(|node| node.to_string()).collect();
+                    println!(
+                        "The {} path from node{} to node{} is: {}",
+                        router.name(),
+                        source,
+                        destination,
+                        nodes.join(" -> ")
+                    );
+                    println!("The distance is {}.", topology.path_distance(&path));
+                }
+                None => println!("No path found from node{} to node{}.", source, destination),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// リンクごとの使用率とボトルネックになった回数の表を書き出し、作ったファイルを返す
+///
+/// 使用率・平均使用帯域・呼損数は実行ごとの値の平均。`blocked_share`は
+/// 全実行の呼損のうちそのリンクがボトルネックだった割合。
+fn write_link_stats(
+    path: &Path,
+    format: Format,
+    metadata: Metadata,
+    topology: &Topology,
+    results: &[RunResult],
+) -> Result<PathBuf, String> {
+    let columns = vec![
+        Column::new("link", ColumnType::Integer),
+        Column::new("node1", ColumnType::Integer),
+        Column::new("node2", ColumnType::Integer),
+        Column::new("capacity", ColumnType::Integer),
+        Column::new("mean_occupancy", ColumnType::Float),
+        Column::new("utilization", ColumnType::Float),
+        Column::new("blocked", ColumnType::Float),
+        Column::new("blocked_share", ColumnType::Float),
+    ];
+    let error = |e: std::io::Error| format!("{}: {}", path.display(), e);
+    let mut writer = ResultWriter::create(path, format, metadata, columns).map_err(error)?;
+    let total_blocked: usize = results.iter().map(RunResult::total_blocked).sum();
+    for (i, link) in topology.links().iter().enumerate() {
+        let average = |value: &dyn Fn(&RunResult) -> f64| {
+            stats::mean(&results.iter().map(value).collect::<Vec<f64>>())
+        };
+        let blocked: usize = results.iter().map(|result| result.links[i].blocked).sum();
+        let share = if total_blocked > 0 {
+            blocked as f64 / total_blocked as f64
+        } else {
+            0.0
+        };
+        writer
+            .write_row(vec![
+                i.into(),
+                link.node1.into(),
+                link.node2.into(),
+                link.capacity.into(),
+                average(&|result| result.mean_occupancy(i)).into(),
+                average(&|result| result.utilization(i, link.capacity)).into(),
+                average(&|result| result.links[i].blocked as f64).into(),
+                share.into(),
+            ])
+            .map_err(error)?;
+    }
+    writer.finish().map_err(error)
+}
+
+/// 方式ごとのJainの公平性指標と最小ホップ数ごとの呼損率を表示する
+fn print_fairness(names: &[&str], stats: &[PairStats]) {
+    println!("Fairness across source-destination pairs:");
+    println!("{:<34} {:>12}", "strategy", "jain_index");
+    for (name, stats) in names.iter().zip(stats) {
+        println!("{:<34} {:>12.6}", name, stats.jain_index());
+    }
+    // 実行回数やウォームアップで除いた呼が方式ごとに違うと、呼が発生したノード対も
+    // 違うので、いずれかの方式に現れたホップ数を全て並べる
+    let hops: BTreeSet<usize> = stats
+        .iter()
+        .flat_map(|stats| stats.by_hops())
+        .map(|stratum| stratum.hops)
+        .collect();
+    println!("\nCall loss rate per minimum hop count:");
+    print!("{:<34}", "strategy");
+    for &hops in &hops {
+        match hops {
+            usize::MAX => print!(" {:>12}", "unreachable"),
+            hops => print!(" {:>12}", format!("hops={}", hops)),
+        }
+    }
+    println!();
+    for (name, stats) in names.iter().zip(stats) {
+        print!("{:<34}", name);
+        for &hops in &hops {
+            let rate = stats.stratum(hops).map(|stratum| stratum.call_loss_rate());
+            print!(" {:>12}", or_dash(rate, 6));
+        }
+        println!();
+    }
+}
+
+/// 方式ごとのノード対ごとの呼損率の表を書き出し、作ったファイルを返す
+///
+/// 到達できないノード対の`hops`は-1とする。
+fn write_pair_stats(
+    path: &Path,
+    format: Format,
+    metadata: Metadata,
+    names: &[&str],
+    stats: &[PairStats],
+) -> Result<PathBuf, String> {
+    let columns = vec![
+        Column::new("strategy", ColumnType::Text),
+        Column::new("source", ColumnType::Integer),
+        Column::new("destination", ColumnType::Integer),
+        Column::new("hops", ColumnType::Integer),
+        Column::new("attempts", ColumnType::Integer),
+        Column::new("blocked", ColumnType::Integer),
+        Column::new("call_loss_rate", ColumnType::Float),
+    ];
+    let error = |e: std::io::Error| format!("{}: {}", path.display(), e);
+    let mut writer = ResultWriter::create(path, format, metadata, columns).map_err(error)?;
+    for (name, stats) in names.iter().zip(stats) {
+        for ((source, destination), result) in stats.pairs() {
+            writer
+                .write_row(vec![
+                    (*name).into(),
+                    source.into(),
+                    destination.into(),
+                    match stats.hops(source, destination) {
+                        usize::MAX => Value::Integer(-1),
+                        hops => hops.into(),
+                    },
+                    result.total_attempts.into(),
+                    (result.total_attempts - result.total_success).into(),
+                    result.call_loss_rate().into(),
+                ])
+                .map_err(error)?;
+        }
+    }
+    writer.finish().map_err(error)
+}
+
+/// 方式ごとの経路の長さの集計と、ホップ数・伸長率の度数分布（呼の割合）を表示する
+fn print_routes(names: &[&str], stats: &[RouteStats]) {
+    println!("Routes of accepted calls:");
+    println!(
+        "{:<34} {:>12} {:>12} {:>12} {:>12} {:>12}",
+        "strategy", "mean_hops", "extra_hops", "mean_dist", "mean_stretch", "max_stretch"
+    );
+    for (name, stats) in names.iter().zip(stats) {
+        println!(
+            "{:<34} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            name,
+            or_dash(stats.mean_hops(), 4),
+            or_dash(stats.mean_extra_hops(), 4),
+            or_dash(stats.mean_distance(), 2),
+            or_dash(stats.mean_stretch(), 4),
+            or_dash(stats.max_stretch(), 4)
+        );
+    }
+
+    let hops: Vec<Vec<(f64, usize)>> = stats
+        .iter()
+        .map(|stats| {
+            let histogram = stats.hop_histogram().into_iter();
+            histogram
+                .map(|(hops, calls)| (hops as f64, calls))
+                .collect()
+        })
+        .collect();
+    print_histogram("hops", 0, names, stats, &hops);
+    let stretch: Vec<Vec<(f64, usize)>> = stats.iter().map(RouteStats::stretch_histogram).collect();
+    print_histogram("stretch", 1, names, stats, &stretch);
+}
+
+/// `value`を小数点以下`precision`桁にした文字列（値が無ければ`-`）
+fn or_dash(value: Option<f64>, precision: usize) -> String {
+    value.map_or_else(
+        || "-".to_string(),
+        |value| format!("{:.*}", precision, value),
+    )
+}
+
+/// 方式ごとの度数分布を、全方式に現れる階級を行にして呼の割合で表示する
+fn print_histogram(
+    title: &str,
+    precision: usize,
+    names: &[&str],
+    stats: &[RouteStats],
+    histograms: &[Vec<(f64, usize)>],
+) {
+    let mut bins: Vec<f64> = histograms.iter().flatten().map(|&(bin, _)| bin).collect();
+    bins.sort_by(f64::total_cmp);
+    bins.dedup();
+    println!("\nShare of accepted calls per {}:", title);
+    print!("{:<12}", title);
+    for name in names {
+        print!(" {:>34}", name);
+    }
+    println!();
+    for bin in bins {
+        print!("{:<12.*}", precision, bin);
+        for (histogram, stats) in histograms.iter().zip(stats) {
+            let calls = histogram
+                .iter()
+                .find(|&&(other, _)| other == bin)
+                .map_or(0, |&(_, calls)| calls);
+            print!(" {:>34.6}", stats.share(calls));
+        }
+        println!();
+    }
+}
+
+/// 方式ごとの経路のホップ数・総距離・伸長率の度数分布を書き出し、作ったファイルを返す
+///
+/// `metric`が`hops`・`distance`の行の`bin`はその値、`stretch`の行は階級の下限。
+/// `share`は方式の確立できた呼のうちその階級に入る割合。
+fn write_route_stats(
+    path: &Path,
+    format: Format,
+    metadata: Metadata,
+    names: &[&str],
+    stats: &[RouteStats],
+) -> Result<PathBuf, String> {
+    let columns = vec![
+        Column::new("strategy", ColumnType::Text),
+        Column::new("metric", ColumnType::Text),
+        Column::new("bin", ColumnType::Float),
+        Column::new("calls", ColumnType::Integer),
+        Column::new("share", ColumnType::Float),
+    ];
+    let error = |e: std::io::Error| format!("{}: {}", path.display(), e);
+    let mut writer = ResultWriter::create(path, format, metadata, columns).map_err(error)?;
+    for (name, stats) in names.iter().zip(stats) {
+        let hops = stats.hop_histogram().into_iter();
+        let distance = stats.distance_histogram().into_iter();
+        let rows = hops
+            .map(|(hops, calls)| ("hops", hops as f64, calls))
+            .chain(distance.map(|(distance, calls)| ("distance", distance as f64, calls)))
+            .chain(
+                stats
+                    .stretch_histogram()
+                    .into_iter()
+                    .map(|(lower, calls)| ("stretch", lower, calls)),
+            );
+        for (metric, bin, calls) in rows {
+            writer
+                .write_row(vec![
+                    (*name).into(),
+                    metric.into(),
+                    bin.into(),
+                    calls.into(),
+                    stats.share(calls).into(),
+                ])
+                .map_err(error)?;
+        }
+    }
+    writer.finish().map_err(error)
+}
+
+/// 実行回数の指定（固定か信頼区間の精度）に従って繰り返し実行する
+fn replicate(
+    simulator: &Simulator,
+    router: &dyn Router,
+    n: usize,
+    args: &RunArgs,
+    seed: u64,
+) -> Vec<RunResult> {
+    match args.stopping_rule() {
+        Some(rule) => simulator.replicate_until(router, n, seed, &rule),
+        None => simulator.replicate(router, n, args.runs, seed),
+    }
+}
+
+/// 実行ごとの呼損率を表示する
+fn print_runs(results: &[RunResult]) {
+    for (run, result) in results.iter().enumerate() {
+        println!(
+            "Call loss rate for run {}: {}",
+            run + 1,
+            result.call_loss_rate()
+        );
+    }
+}
+
+/// 平均呼損率とその信頼区間を表示する
+fn print_summary(n: usize, args: &RunArgs, results: &[RunResult]) -> ConfidenceInterval {
+    let values: Vec<f64> = results.iter().map(|r| r.call_loss_rate()).collect();
+    let interval = ConfidenceInterval::new(&values, args.confidence);
+    println!("Average call loss rate for n = {}: {}", n, interval.mean);
+    println!(
+        "  std dev = {}, {}% CI = [{}, {}] ({} runs)",
+        interval.std_dev,
+        args.confidence * 100.0,
+        interval.lower(),
+        interval.upper(),
+        results.len()
+    );
+    if args.warmup != Warmup::None {
+        let warmup: Vec<f64> = results.iter().map(|r| r.warmup as f64).collect();
+        println!(
+            "  warm-up: {} calls removed on average",
+            stats::mean(&warmup)
+        );
+    }
+    if let Some(rule) = args.stopping_rule() {
+        if interval.relative_half_width() > rule.relative_half_width {
+            println!(
+                "  relative half-width {} did not reach {} within {} runs",
+                interval.relative_half_width(),
+                rule.relative_half_width,
+                rule.max_runs
+            );
+        }
+    }
+    print_class_losses(&args.classes, results);
+    interval
+}
+
+/// クラスが複数あるときにクラスごとの平均呼損率を表示する
+fn print_class_losses(classes: &TrafficClasses, results: &[RunResult]) {
+    if classes.len() < 2 {
+        return;
+    }
+    for (i, class) in classes.classes().iter().enumerate() {
+        match class_average(results, i) {
+            Some(average) => println!("  class {} (demand = {}): {}", i, class.demand, average),
+            None => println!("  class {} (demand = {}): no calls", i, class.demand),
+        }
+    }
+}
+
+/// クラス`class`の呼損率の平均（呼が発生しなかった実行は除き、全て除けば`None`）
+fn class_average(results: &[RunResult], class: usize) -> Option<f64> {
+    let values: Vec<f64> = results
+        .iter()
+        .map(|r| r.classes[class])
+        .filter(|c| c.total_attempts > 0)
+        .map(|c| c.call_loss_rate())
+        .collect();
+    (!values.is_empty()).then(|| stats::mean(&values))
+}
+
+fn positive(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(0) => Err("must be at least 1".to_string()),
+        Ok(number) => Ok(number),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn probability(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(p) if p > 0.0 && p < 1.0 => Ok(p),
+        Ok(_) => Err("must be between 0 and 1".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+fn positive_rate(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(rate) if rate > 0.0 && rate.is_finite() => Ok(rate),
+        Ok(_) => Err("must be a positive number".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
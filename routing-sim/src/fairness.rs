@@ -0,0 +1,105 @@
+//! ノード対ごとの呼損率と公平性
+//!
+//! 実行ごとのノード対の結果は対あたりの呼の数が少ないので、全実行で合計してから
+//! 対ごとの呼損率、最小ホップ数ごとの呼損率、Jainの公平性指標を求める。
+
+use std::collections::BTreeMap;
+
+use crate::simulator::{PairResult, RunResult};
+use crate::stats;
+use crate::topology::Topology;
+
+/// 全実行で合計したノード対ごとの結果
+#[derive(Clone, Debug)]
+pub struct PairStats {
+    node_num: usize,
+    /// 最小ホップ数（`source * node_num + destination`）
+    hops: Vec<usize>,
+    /// 呼が発生したノード対の結果
+    pairs: BTreeMap<(usize, usize), PairResult>,
+}
+
+/// 最小ホップ数が同じノード対をまとめた結果
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HopStratum {
+    /// 最小ホップ数
+    pub hops: usize,
+    /// 呼が発生したノード対の数
+    pub pairs: usize,
+    /// 発生した呼の数
+    pub total_attempts: usize,
+    /// 確立できた呼の数
+    pub total_success: usize,
+}
+
+impl HopStratum {
+    /// 呼損率（呼の数で重み付けしたもの）
+    pub fn call_loss_rate(&self) -> f64 {
+        (self.total_attempts - self.total_success) as f64 / self.total_attempts as f64
+    }
+}
+
+impl PairStats {
+    pub fn new(topology: &Topology, results: &[RunResult]) -> Self {
+        let mut pairs: BTreeMap<(usize, usize), PairResult> = BTreeMap::new();
+        for result in results {
+            for (&pair, pair_result) in &result.pairs {
+                let total = pairs.entry(pair).or_default();
+                total.total_attempts += pair_result.total_attempts;
+                total.total_success += pair_result.total_success;
+            }
+        }
+        PairStats {
+            node_num: topology.node_num(),
+            hops: topology.hop_counts(),
+            pairs,
+        }
+    }
+
+    /// ノード対の最小ホップ数
+    pub fn hops(&self, source: usize, destination: usize) -> usize {
+        self.hops[source * self.node_num + destination]
+    }
+
+    /// 呼が発生したノード対の（始点, 終点）と結果（始点・終点の順）
+    pub fn pairs(&self) -> impl Iterator<Item = ((usize, usize), &PairResult)> {
+        self.pairs.iter().map(|(&pair, result)| (pair, result))
+    }
+
+    /// 最小ホップ数ごとの結果（ホップ数の小さい順）
+    pub fn by_hops(&self) -> Vec<HopStratum> {
+        let mut strata: BTreeMap<usize, HopStratum> = BTreeMap::new();
+        for (&(source, destination), result) in &self.pairs {
+            let hops = self.hops(source, destination);
+            let stratum = strata.entry(hops).or_insert(HopStratum {
+                hops,
+                pairs: 0,
+                total_attempts: 0,
+                total_success: 0,
+            });
+            stratum.pairs += 1;
+            stratum.total_attempts += result.total_attempts;
+            stratum.total_success += result.total_success;
+        }
+        strata.into_values().collect()
+    }
+
+    /// 最小ホップ数が`hops`のノード対の結果（呼が発生した対が無ければ`None`）
+    pub fn stratum(&self, hops: usize) -> Option<HopStratum> {
+        self.by_hops()
+            .into_iter()
+            .find(|stratum| stratum.hops == hops)
+    }
+
+    /// ノード対ごとの呼の確立率（1 - 呼損率）に対するJainの公平性指標
+    ///
+    /// 全ての対が同じ確立率なら1、1つの対だけが呼を確立できれば`1 / 対の数`。
+    pub fn jain_index(&self) -> f64 {
+        let acceptance: Vec<f64> = self
+            .pairs
+            .values()
+            .map(|result| 1.0 - result.call_loss_rate())
+            .collect();
+        stats::jain_index(&acceptance)
+    }
+}
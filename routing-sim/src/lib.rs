@@ -0,0 +1,26 @@
+//! 経路制御方式ごとの呼損率シミュレーションで共通に使う部品
+//!
+//! `distance.txt` から読み込んだ [`Topology`] の上で、[`Router`] を実装した
+//! 経路制御方式を [`Simulator`] に渡すと呼損率を評価できる。
+//! 新しい方式を追加する場合は [`Router`] を実装するだけでよい。
+
+pub mod analytic;
+pub mod cli;
+pub mod fairness;
+pub mod output;
+pub mod process;
+pub mod reservation;
+pub mod rng;
+pub mod router;
+pub mod routes;
+pub mod simulator;
+pub mod stats;
+pub mod topology;
+pub mod traffic;
+
+pub use router::Router;
+pub use simulator::{
+    Call, ClassResult, LinkResult, PairResult, RouteResult, RunResult, Simulator, Warmup,
+};
+pub use topology::{Topology, TopologyError};
+pub use traffic::TrafficMatrix;
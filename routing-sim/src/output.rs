@@ -0,0 +1,376 @@
+//! 実験結果のファイル出力
+//!
+//! 実験ごとに新しいファイルを作り、方式・トポロジのハッシュ値・シード・
+//! 実行回数・パラメータと列の型（スキーマ）を結果と一緒に記録する。
+//! 同じ名前のファイルがあれば`results-1.csv`のように番号を付けた名前にし、
+//! 既存のファイルには追記しない。
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde_json::{json, Map, Value as Json};
+
+/// 結果ファイルの形式
+///
+/// 文字列からは `csv`、`jsonl`、`columnar` の形式で作る。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// `#`で始まるメタデータ行、見出し行、結果の行
+    #[default]
+    Csv,
+    /// 1行目がメタデータ、以降が1行1結果のJSONオブジェクト
+    JsonLines,
+    /// メタデータと列ごとの値の配列からなる1つのJSONオブジェクト
+    Columnar,
+}
+
+impl Format {
+    /// 形式の標準の拡張子
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::JsonLines => "jsonl",
+            Format::Columnar => "json",
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Format::Csv => write!(f, "csv"),
+            Format::JsonLines => write!(f, "jsonl"),
+            Format::Columnar => write!(f, "columnar"),
+        }
+    }
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        match spec {
+            "csv" => Ok(Format::Csv),
+            "jsonl" => Ok(Format::JsonLines),
+            "columnar" => Ok(Format::Columnar),
+            _ => Err(format!(
+                "unknown format `{}` (expected csv, jsonl or columnar)",
+                spec
+            )),
+        }
+    }
+}
+
+/// 列の型
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnType {
+    Integer,
+    Float,
+    Text,
+}
+
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnType::Integer => write!(f, "integer"),
+            ColumnType::Float => write!(f, "float"),
+            ColumnType::Text => write!(f, "text"),
+        }
+    }
+}
+
+/// 結果の列
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Column {
+    pub name: String,
+    pub kind: ColumnType,
+}
+
+impl Column {
+    pub fn new(name: &str, kind: ColumnType) -> Self {
+        Column {
+            name: name.to_string(),
+            kind,
+        }
+    }
+}
+
+/// 結果の1つの値
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl Value {
+    fn kind(&self) -> ColumnType {
+        match self {
+            Value::Integer(_) => ColumnType::Integer,
+            Value::Float(_) => ColumnType::Float,
+            Value::Text(_) => ColumnType::Text,
+        }
+    }
+
+    /// JSONの値（有限でない実数は`null`）
+    fn to_json(&self) -> Json {
+        match self {
+            Value::Integer(value) => json!(value),
+            Value::Float(value) => json!(value),
+            Value::Text(value) => json!(value),
+        }
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Self {
+        Value::Integer(value as i64)
+    }
+}
+
+impl From<isize> for Value {
+    fn from(value: isize) -> Self {
+        Value::Integer(value as i64)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Text(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Text(value)
+    }
+}
+
+/// CSVの1つのフィールド（区切り文字や引用符を含む文字列は引用符で囲む）
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Integer(value) => write!(f, "{}", value),
+            Value::Float(value) => write!(f, "{}", value),
+            Value::Text(value) if value.contains([',', '"', '\n']) => {
+                write!(f, "\"{}\"", value.replace('"', "\"\""))
+            }
+            Value::Text(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+/// 実験の条件
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metadata {
+    /// 経路制御方式
+    pub strategy: String,
+    /// トポロジファイル
+    pub topology: String,
+    /// [`Topology::fingerprint`](crate::Topology::fingerprint)
+    pub topology_hash: u64,
+    /// 乱数のマスターシード
+    pub seed: u64,
+    /// 実行回数（固定の回数、または停止規則の説明）
+    pub replications: String,
+    /// その他のパラメータ（名前, 値）
+    pub parameters: Vec<(String, String)>,
+}
+
+impl Metadata {
+    fn to_json(&self, columns: &[Column]) -> Json {
+        let parameters: Map<String, Json> = self
+            .parameters
+            .iter()
+            .map(|(name, value)| (name.clone(), json!(value)))
+            .collect();
+        let schema: Vec<Json> = columns
+            .iter()
+            .map(|column| json!({"name": column.name, "type": column.kind.to_string()}))
+            .collect();
+        json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "strategy": self.strategy,
+            "topology": self.topology,
+            "topology_hash": format!("{:016x}", self.topology_hash),
+            "seed": self.seed,
+            "replications": self.replications,
+            "parameters": parameters,
+            "schema": schema,
+        })
+    }
+}
+
+/// 実験ごとに新しいファイルへ結果を書き出す
+///
+/// CSVとJSON Linesは行を書くたびにファイルへ反映するので、途中で止めても
+/// それまでの結果は残る。columnarは列ごとの値をメモリに溜め、
+/// [`ResultWriter::finish`]（呼ばなければ破棄されるとき）に1回だけ書く。
+pub struct ResultWriter {
+    format: Format,
+    path: PathBuf,
+    file: BufWriter<File>,
+    metadata: Metadata,
+    columns: Vec<Column>,
+    /// columnarで書く列ごとの値
+    values: Vec<Vec<Json>>,
+    /// columnarの文書を書いたか
+    finished: bool,
+}
+
+impl ResultWriter {
+    /// `path`（あれば番号を付けた名前）に新しいファイルを作り、メタデータを書く
+    pub fn create(
+        path: &Path,
+        format: Format,
+        metadata: Metadata,
+        columns: Vec<Column>,
+    ) -> io::Result<Self> {
+        let (file, path) = create_fresh(path)?;
+        let mut writer = ResultWriter {
+            format,
+            path,
+            file: BufWriter::new(file),
+            metadata,
+            values: vec![Vec::new(); columns.len()],
+            columns,
+            finished: false,
+        };
+        match format {
+            Format::Csv => writer.write_csv_header()?,
+            Format::JsonLines => {
+                let line = json!({"metadata": writer.metadata.to_json(&writer.columns)});
+                writeln!(writer.file, "{}", line)?;
+            }
+            Format::Columnar => {}
+        }
+        writer.file.flush()?;
+        Ok(writer)
+    }
+
+    /// 実際に作ったファイル
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 1行の結果を書く（値の数と型は列と同じであること）
+    pub fn write_row(&mut self, row: Vec<Value>) -> io::Result<()> {
+        assert_eq!(row.len(), self.columns.len(), "row must match the columns");
+        for (value, column) in row.iter().zip(&self.columns) {
+            assert_eq!(value.kind(), column.kind, "column `{}`", column.name);
+        }
+        match self.format {
+            Format::Csv => {
+                let fields: Vec<String> = row.iter().map(|value| value.to_string()).collect();
+                writeln!(self.file, "{}", fields.join(","))?;
+            }
+            Format::JsonLines => {
+                let object: Map<String, Json> = self
+                    .columns
+                    .iter()
+                    .zip(&row)
+                    .map(|(column, value)| (column.name.clone(), value.to_json()))
+                    .collect();
+                writeln!(self.file, "{}", Json::Object(object))?;
+            }
+            Format::Columnar => {
+                for (values, value) in self.values.iter_mut().zip(&row) {
+                    values.push(value.to_json());
+                }
+                return Ok(());
+            }
+        }
+        self.file.flush()
+    }
+
+    /// 残りを書き出してファイルを閉じ、実際に作ったファイルを返す
+    pub fn finish(mut self) -> io::Result<PathBuf> {
+        self.write_columnar()?;
+        Ok(self.path.clone())
+    }
+
+    fn write_csv_header(&mut self) -> io::Result<()> {
+        let metadata = &self.metadata;
+        writeln!(self.file, "# version={}", env!("CARGO_PKG_VERSION"))?;
+        writeln!(self.file, "# strategy={}", metadata.strategy)?;
+        writeln!(self.file, "# topology={}", metadata.topology)?;
+        writeln!(self.file, "# topology_hash={:016x}", metadata.topology_hash)?;
+        writeln!(self.file, "# seed={}", metadata.seed)?;
+        writeln!(self.file, "# replications={}", metadata.replications)?;
+        for (name, value) in &metadata.parameters {
+            writeln!(self.file, "# {}={}", name, value)?;
+        }
+        let types: Vec<String> = self.columns.iter().map(|c| c.kind.to_string()).collect();
+        writeln!(self.file, "# types={}", types.join(","))?;
+        let names: Vec<&str> = self.columns.iter().map(|c| c.name.as_str()).collect();
+        writeln!(self.file, "{}", names.join(","))
+    }
+
+    /// columnarなら溜めた値を1つの文書として書く（2回目以降は何もしない）
+    fn write_columnar(&mut self) -> io::Result<()> {
+        if self.format != Format::Columnar || self.finished {
+            return Ok(());
+        }
+        self.finished = true;
+        let columns: Vec<Json> = self
+            .columns
+            .iter()
+            .zip(std::mem::take(&mut self.values))
+            .map(|(column, values)| json!({"name": column.name, "values": values}))
+            .collect();
+        let document = json!({
+            "metadata": self.metadata.to_json(&self.columns),
+            "columns": columns,
+        });
+        serde_json::to_writer(&mut self.file, &document)?;
+        writeln!(self.file)?;
+        self.file.flush()
+    }
+}
+
+impl Drop for ResultWriter {
+    /// [`ResultWriter::finish`]を呼ばずに破棄されても結果を残す（エラーは無視する）
+    fn drop(&mut self) {
+        let _ = self.write_columnar();
+    }
+}
+
+/// `path`が無ければそれを、あれば`名前-1.拡張子`、`名前-2.拡張子`…のうち
+/// 最初に無いものを新しく作る
+fn create_fresh(path: &Path) -> io::Result<(File, PathBuf)> {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().into_owned());
+    let mut candidate = path.to_path_buf();
+    for i in 1.. {
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&candidate)
+        {
+            Ok(file) => return Ok((file, candidate)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                let name = match &extension {
+                    Some(extension) => format!("{}-{}.{}", stem, i, extension),
+                    None => format!("{}-{}", stem, i),
+                };
+                candidate = path.with_file_name(name);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!("some numbered file name is free")
+}
@@ -0,0 +1,269 @@
+//! 呼の到着過程と保持時間分布
+
+use std::fmt;
+use std::str::FromStr;
+
+use rand::Rng;
+use rand_distr::{Distribution, Exp, LogNormal};
+
+/// 呼の到着過程
+///
+/// 文字列からは `poisson`、`deterministic`、`mmpp:λ0,λ1,r0,r1` の形式で作る。
+/// 前の2つの到着率は [`ArrivalProcess::with_rate`] で与える。
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArrivalProcess {
+    /// 到着率`rate`のポアソン到着
+    Poisson { rate: f64 },
+    /// 間隔`1 / rate`の等間隔到着
+    Deterministic { rate: f64 },
+    /// 2状態のマルコフ変調ポアソン過程
+    ///
+    /// 状態`i`では到着率`rates[i]`でポアソン到着し、率`switch[i]`で他方の状態へ移る。
+    Mmpp { rates: [f64; 2], switch: [f64; 2] },
+}
+
+impl ArrivalProcess {
+    /// 到着率を`rate`に置き換える（MMPPは到着率を個別に指定するので変えない）
+    pub fn with_rate(self, rate: f64) -> Self {
+        match self {
+            ArrivalProcess::Poisson { .. } => ArrivalProcess::Poisson { rate },
+            ArrivalProcess::Deterministic { .. } => ArrivalProcess::Deterministic { rate },
+            mmpp @ ArrivalProcess::Mmpp { .. } => mmpp,
+        }
+    }
+
+    /// 長時間平均の到着率を`rate`にする（MMPPは両状態の到着率を同じ倍率で変える）
+    pub fn with_mean_rate(self, rate: f64) -> Self {
+        match self {
+            ArrivalProcess::Mmpp { rates, switch } => {
+                let scale = rate / self.mean_rate();
+                ArrivalProcess::Mmpp {
+                    rates: [rates[0] * scale, rates[1] * scale],
+                    switch,
+                }
+            }
+            process => process.with_rate(rate),
+        }
+    }
+
+    /// 長時間平均の到着率
+    pub fn mean_rate(&self) -> f64 {
+        match *self {
+            ArrivalProcess::Poisson { rate } | ArrivalProcess::Deterministic { rate } => rate,
+            ArrivalProcess::Mmpp { rates, switch } => {
+                // 状態0に滞在する割合は switch[1] / (switch[0] + switch[1])
+                (rates[0] * switch[1] + rates[1] * switch[0]) / (switch[0] + switch[1])
+            }
+        }
+    }
+
+    /// 到着時刻を順に生成する
+    pub fn arrivals(&self) -> Arrivals {
+        Arrivals {
+            process: *self,
+            time: 0.0,
+            count: 0,
+            state: 0,
+        }
+    }
+}
+
+impl Default for ArrivalProcess {
+    /// 1タイムユニットごとに1つ到着する
+    fn default() -> Self {
+        ArrivalProcess::Deterministic { rate: 1.0 }
+    }
+}
+
+/// [`ArrivalProcess`] の到着時刻の列
+#[derive(Clone, Debug)]
+pub struct Arrivals {
+    process: ArrivalProcess,
+    time: f64,
+    count: u64,
+    /// MMPPの現在の状態
+    state: usize,
+}
+
+impl Arrivals {
+    /// 次の到着時刻
+    pub fn next_time<R: Rng>(&mut self, rng: &mut R) -> f64 {
+        match self.process {
+            ArrivalProcess::Poisson { rate } => {
+                self.time += Exp::new(rate).expect("rate must be positive").sample(rng);
+            }
+            ArrivalProcess::Deterministic { rate } => {
+                // 誤差が積み重ならないように到着番号から計算する
+                self.count += 1;
+                self.time = self.count as f64 / rate;
+            }
+            ArrivalProcess::Mmpp { rates, switch } => loop {
+                let rate = rates[self.state] + switch[self.state];
+                self.time += Exp::new(rate).expect("rate must be positive").sample(rng);
+                if rng.gen::<f64>() * rate < rates[self.state] {
+                    break;
+                }
+                self.state = 1 - self.state;
+            },
+        }
+        self.time
+    }
+}
+
+/// 呼の保持時間分布（平均は評価点ごとに与える）
+///
+/// 文字列からは `exponential`、`deterministic`、`lognormal:σ` の形式で作る。
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum HoldingTime {
+    /// 指数分布
+    Exponential,
+    /// 常に平均値
+    #[default]
+    Deterministic,
+    /// 対数の標準偏差が`sigma`の対数正規分布
+    LogNormal { sigma: f64 },
+}
+
+impl HoldingTime {
+    /// 平均`mean`の保持時間を1つ生成する
+    pub fn sample<R: Rng>(&self, mean: f64, rng: &mut R) -> f64 {
+        if mean <= 0.0 {
+            return 0.0;
+        }
+        match *self {
+            HoldingTime::Exponential => Exp::new(1.0 / mean).expect("mean is positive").sample(rng),
+            HoldingTime::Deterministic => mean,
+            HoldingTime::LogNormal { sigma } => {
+                // 平均が`mean`になるように対数の平均を決める
+                let mu = mean.ln() - sigma * sigma / 2.0;
+                LogNormal::new(mu, sigma)
+                    .expect("sigma must be finite")
+                    .sample(rng)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ArrivalProcess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArrivalProcess::Poisson { rate } => write!(f, "poisson(rate={})", rate),
+            ArrivalProcess::Deterministic { rate } => write!(f, "deterministic(rate={})", rate),
+            ArrivalProcess::Mmpp { rates, switch } => write!(
+                f,
+                "mmpp:{},{},{},{}",
+                rates[0], rates[1], switch[0], switch[1]
+            ),
+        }
+    }
+}
+
+impl fmt::Display for HoldingTime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HoldingTime::Exponential => write!(f, "exponential"),
+            HoldingTime::Deterministic => write!(f, "deterministic"),
+            HoldingTime::LogNormal { sigma } => write!(f, "lognormal:{}", sigma),
+        }
+    }
+}
+
+/// 到着過程・保持時間分布の指定の誤り
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseProcessError(String);
+
+impl fmt::Display for ParseProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for ParseProcessError {}
+
+/// `name:a,b,...` の形式を名前と数値の列に分ける。数値はすべて正であること
+fn split_spec(spec: &str) -> Result<(&str, Vec<f64>), ParseProcessError> {
+    let (name, params) = match spec.split_once(':') {
+        Some((name, params)) => (name, params),
+        None => (spec, ""),
+    };
+    let params = params
+        .split(',')
+        .filter(|param| !param.is_empty())
+        .map(|param| match param.trim().parse::<f64>() {
+            Ok(value) if value > 0.0 && value.is_finite() => Ok(value),
+            _ => Err(ParseProcessError(format!(
+                "parameter must be a positive number: {}",
+                param
+            ))),
+        })
+        .collect::<Result<Vec<f64>, _>>()?;
+    Ok((name, params))
+}
+
+fn expect_params(spec: &str, params: &[f64], count: usize) -> Result<(), ParseProcessError> {
+    if params.len() == count {
+        Ok(())
+    } else {
+        Err(ParseProcessError(format!(
+            "`{}` takes {} parameter(s), found {}",
+            spec,
+            count,
+            params.len()
+        )))
+    }
+}
+
+impl FromStr for ArrivalProcess {
+    type Err = ParseProcessError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (name, params) = split_spec(spec)?;
+        match name {
+            "poisson" => {
+                expect_params(spec, &params, 0)?;
+                Ok(ArrivalProcess::Poisson { rate: 1.0 })
+            }
+            "deterministic" => {
+                expect_params(spec, &params, 0)?;
+                Ok(ArrivalProcess::Deterministic { rate: 1.0 })
+            }
+            "mmpp" => {
+                expect_params(spec, &params, 4)?;
+                Ok(ArrivalProcess::Mmpp {
+                    rates: [params[0], params[1]],
+                    switch: [params[2], params[3]],
+                })
+            }
+            _ => Err(ParseProcessError(format!(
+                "unknown arrival process `{}` (expected poisson, deterministic or mmpp:l0,l1,r0,r1)",
+                name
+            ))),
+        }
+    }
+}
+
+impl FromStr for HoldingTime {
+    type Err = ParseProcessError;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let (name, params) = split_spec(spec)?;
+        match name {
+            "exponential" => {
+                expect_params(spec, &params, 0)?;
+                Ok(HoldingTime::Exponential)
+            }
+            "deterministic" => {
+                expect_params(spec, &params, 0)?;
+                Ok(HoldingTime::Deterministic)
+            }
+            "lognormal" => {
+                expect_params(spec, &params, 1)?;
+                Ok(HoldingTime::LogNormal { sigma: params[0] })
+            }
+            _ => Err(ParseProcessError(format!(
+                "unknown holding time distribution `{}` (expected exponential, deterministic or lognormal:sigma)",
+                name
+            ))),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! 迂回呼に対するトランク予約
+//!
+//! ノード対の最小ホップ経路よりホップ数の多い経路（迂回経路）を使う呼は、
+//! 呼を受け付けた後もリンクの空き容量が予約量以上残る場合にだけ
+//! そのリンクを使える。最小ホップ経路を使う呼には予約は関係しない。
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::topology::Topology;
+
+/// リンクごとの予約量とノード対ごとの最小ホップ数
+#[derive(Clone, Debug)]
+pub struct TrunkReservation {
+    topology: Topology,
+    /// 予約量（添字はリンク番号）
+    thresholds: Vec<isize>,
+    /// 最小ホップ数（`source * node_num + destination`、到達できなければ`usize::MAX`）
+    min_hops: Vec<usize>,
+}
+
+impl TrunkReservation {
+    /// 全リンクの予約量を`threshold`とする
+    pub fn new(topology: &Topology, threshold: isize) -> Self {
+        TrunkReservation {
+            topology: topology.clone(),
+            thresholds: vec![threshold; topology.links().len()],
+            min_hops: topology.hop_counts(),
+        }
+    }
+
+    /// リンク`link`の予約量を`threshold`にする
+    pub fn set_threshold(&mut self, link: usize, threshold: isize) {
+        self.thresholds[link] = threshold;
+    }
+
+    /// リンク`link`の予約量
+    pub fn threshold(&self, link: usize) -> isize {
+        self.thresholds[link]
+    }
+
+    /// ノード対の最小ホップ数
+    pub fn min_hops(&self, source: usize, destination: usize) -> usize {
+        self.min_hops[source * self.topology.node_num() + destination]
+    }
+
+    /// `source`から`destination`への帯域`demand`の呼が、空き容量`bandwidth`の
+    /// もとでリンク列`links`の経路を使えるか
+    pub fn admits(
+        &self,
+        source: usize,
+        destination: usize,
+        links: &[usize],
+        bandwidth: &[isize],
+        demand: isize,
+    ) -> bool {
+        if links.len() <= self.min_hops(source, destination) {
+            return links.iter().all(|&link| bandwidth[link] >= demand);
+        }
+        links
+            .iter()
+            .all(|&link| bandwidth[link] - demand >= self.thresholds[link])
+    }
+
+    /// ノード列`path`の経路を使えるか（[`TrunkReservation::admits`]と同じ条件）
+    pub fn admits_path(&self, path: &[usize], bandwidth: &[isize], demand: isize) -> bool {
+        match (path.first(), path.last(), self.topology.path_links(path)) {
+            (Some(&source), Some(&destination), Some(links)) => {
+                self.admits(source, destination, &links, bandwidth, demand)
+            }
+            _ => false,
+        }
+    }
+
+    /// 空き容量`bandwidth`から予約量を除いた容量
+    ///
+    /// この容量で帯域分の空きがあるリンクだけを通る経路は、ホップ数によらず
+    /// 予約の条件を満たす。
+    pub fn unreserved(&self, bandwidth: &[isize]) -> Vec<isize> {
+        bandwidth
+            .iter()
+            .zip(&self.thresholds)
+            .map(|(bandwidth, threshold)| bandwidth - threshold)
+            .collect()
+    }
+}
+
+/// リンクごとの予約量の指定（`ノード1-ノード2=予約量`）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LinkThreshold {
+    pub node1: usize,
+    pub node2: usize,
+    pub threshold: isize,
+}
+
+impl fmt::Display for LinkThreshold {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}={}", self.node1, self.node2, self.threshold)
+    }
+}
+
+impl FromStr for LinkThreshold {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid link reservation `{}` (expected node1-node2=threshold)",
+                spec
+            )
+        };
+        let (link, threshold) = spec.split_once('=').ok_or_else(invalid)?;
+        let (node1, node2) = link.split_once('-').ok_or_else(invalid)?;
+        let threshold: isize = threshold.trim().parse().map_err(|_| invalid())?;
+        if threshold < 0 {
+            return Err(format!("reservation must not be negative: {}", threshold));
+        }
+        Ok(LinkThreshold {
+            node1: node1.trim().parse().map_err(|_| invalid())?,
+            node2: node2.trim().parse().map_err(|_| invalid())?,
+            threshold,
+        })
+    }
+}
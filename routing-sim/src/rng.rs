@@ -0,0 +1,25 @@
+//! 再現可能な乱数列
+//!
+//! 乱数はすべてマスターシードから作る。評価点（保持時間`n`など）と
+//! 実行番号ごとにChaChaの別ストリームを割り当てるので、各実行の乱数列は
+//! 互いに独立で、実行する順序や範囲を変えても同じ値になる。
+
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// シミュレーションで使う乱数生成器
+pub type SimRng = ChaCha8Rng;
+
+/// マスターシード`seed`から、評価点`point`の`replication`回目の実行用の乱数列を作る
+///
+/// `point`と`replication`はそれぞれ2^32未満であれば別々のストリームになる。
+pub fn stream(seed: u64, point: u64, replication: u64) -> SimRng {
+    let mut rng = SimRng::seed_from_u64(seed);
+    rng.set_stream((point << 32) | (replication & 0xffff_ffff));
+    rng
+}
+
+/// シードが指定されなかった場合に使うシードをOSの乱数から作る
+pub fn random_seed() -> u64 {
+    rand::rngs::OsRng.next_u64()
+}
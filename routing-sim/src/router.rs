@@ -0,0 +1,162 @@
+use crate::reservation::TrunkReservation;
+use crate::topology::Topology;
+
+mod dijkstra;
+mod dynamic_alternative;
+mod k_shortest_path;
+mod least_loaded;
+mod maximum_shortest_path;
+mod maximum_shortest_path_on_demand;
+mod route_cache;
+mod shortest_path;
+mod shortest_path_on_demand;
+mod width;
+
+pub use dijkstra::QueueKind;
+pub use dynamic_alternative::DynamicAlternative;
+pub use k_shortest_path::{k_shortest_paths, KShortestPath};
+pub use least_loaded::LeastLoaded;
+pub use maximum_shortest_path::MaximumShortestPath;
+pub use maximum_shortest_path_on_demand::MaximumShortestPathOnDemand;
+pub use shortest_path::ShortestPath;
+pub use shortest_path_on_demand::ShortestPathOnDemand;
+pub use width::{path_width, WidthMode};
+
+/// 経路制御方式
+///
+/// 呼が発生するたびに [`Simulator`](crate::Simulator) から呼び出され、
+/// 始点から終点までの経路（ノード列）を返す。返した経路上のリンクに
+/// 呼の帯域分の空き容量が無ければ、その呼は呼損になる。
+///
+/// 並列に実行するときは実行ごとに [`RouterClone::clone_box`] で複製する。
+pub trait Router: RouterClone + Send + Sync {
+    /// 方式名
+    fn name(&self) -> &'static str;
+
+    /// リンクの空き容量が`bandwidth`（添字はリンク番号）のときに、
+    /// 帯域`demand`の呼の経路を求める。経路が無ければ`None`
+    fn find_path(
+        &mut self,
+        source: usize,
+        destination: usize,
+        bandwidth: &[isize],
+        demand: isize,
+    ) -> Option<Vec<usize>>;
+
+    /// トランク予約`reservation`のもとで、帯域`demand`の呼を受け付けられる経路を求める
+    ///
+    /// 既定では [`Router::find_path`] の経路が予約の条件を満たさなければ、
+    /// 予約量を除いた空き容量（[`TrunkReservation::unreserved`]）で探し直す。
+    /// 候補の経路を持つ方式は、条件を満たす候補を選ぶように上書きする。
+    fn find_reserved_path(
+        &mut self,
+        source: usize,
+        destination: usize,
+        bandwidth: &[isize],
+        demand: isize,
+        reservation: &TrunkReservation,
+    ) -> Option<Vec<usize>> {
+        let path = self.find_path(source, destination, bandwidth, demand)?;
+        if self.is_fixed() || reservation.admits_path(&path, bandwidth, demand) {
+            return Some(path);
+        }
+        let unreserved = reservation.unreserved(bandwidth);
+        self.find_path(source, destination, &unreserved, demand)
+            .or(Some(path))
+    }
+
+    /// 実行の開始時に呼ばれる。呼をまたいで状態を持つ方式は、
+    /// 状態と乱数列を`seed`から初期化する
+    fn reset(&mut self, _seed: u64) {}
+
+    /// 呼損になった呼（経路が無いか、経路を受け付けられなかった）を通知する
+    fn blocked(&mut self, _source: usize, _destination: usize) {}
+
+    /// 空き容量によらず経路が決まる固定経路方式なら`true`
+    fn is_fixed(&self) -> bool {
+        false
+    }
+}
+
+/// 経路制御方式をトレイトオブジェクトのまま複製する
+///
+/// `Clone`を実装した方式には自動で実装される。
+pub trait RouterClone {
+    fn clone_box(&self) -> Box<dyn Router>;
+}
+
+impl<T: Router + Clone + 'static> RouterClone for T {
+    fn clone_box(&self) -> Box<dyn Router> {
+        Box::new(self.clone())
+    }
+}
+
+/// 利用できる経路制御方式の名前
+pub const STRATEGIES: &[&str] = &[
+    "shortest-path",
+    "shortest-path-on-demand",
+    "maximum-shortest-path",
+    "maximum-shortest-path-on-demand",
+    "k-shortest-path",
+    "least-loaded",
+    "dynamic-alternative",
+];
+
+/// 方式ごとのパラメータ
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    /// k-shortest-path・least-loaded・dynamic-alternativeの候補経路の数
+    pub k: usize,
+    /// maximum-shortest-path(-on-demand)の経路の選び方
+    pub width: WidthMode,
+    /// shortest-path-on-demandの探索に使うキュー
+    pub queue: QueueKind,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            k: 3,
+            width: WidthMode::default(),
+            queue: QueueKind::default(),
+        }
+    }
+}
+
+/// 空き容量によらない距離の最短経路。到達できなければ`None`
+pub(crate) fn shortest_path(
+    topology: &Topology,
+    source: usize,
+    destination: usize,
+) -> Option<Vec<usize>> {
+    dijkstra::shortest_tree(
+        topology,
+        source,
+        Some(destination),
+        |_| true,
+        QueueKind::BinaryHeap,
+    )
+    .path(destination)
+}
+
+/// 方式名から既定のパラメータで経路制御方式を作る。未知の名前なら`None`
+pub fn build(name: &str, topology: &Topology) -> Option<Box<dyn Router>> {
+    build_with(name, topology, &Options::default())
+}
+
+/// 方式名とパラメータから経路制御方式を作る。未知の名前なら`None`
+pub fn build_with(name: &str, topology: &Topology, options: &Options) -> Option<Box<dyn Router>> {
+    let router: Box<dyn Router> = match name {
+        "shortest-path" => Box::new(ShortestPath::new(topology)),
+        "shortest-path-on-demand" => Box::new(ShortestPathOnDemand::new(topology, options.queue)),
+        "maximum-shortest-path" => Box::new(MaximumShortestPath::new(topology, options.width)),
+        "maximum-shortest-path-on-demand" => {
+            Box::new(MaximumShortestPathOnDemand::new(topology, options.width))
+        }
+        "k-shortest-path" => Box::new(KShortestPath::new(topology, options.k)),
+        "least-loaded" => Box::new(LeastLoaded::new(topology, options.k)),
+        "dynamic-alternative" => Box::new(DynamicAlternative::new(topology, options.k)),
+        _ => return None,
+    };
+    Some(router)
+}
@@ -0,0 +1,256 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::topology::{Adjacent, Topology};
+
+/// 距離だけで経路を評価する探索に使う優先度付きキューの種類
+///
+/// どちらも同じ経路を返す。bucketはリンクの距離が小さな整数のときに速い。
+/// リンクの距離の最大値がリンク数より大きいとバケットの数が多くなりすぎるので、
+/// bucketを指定しても二分ヒープを使う。
+/// 文字列からは `heap`、`bucket` の形式で作る。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QueueKind {
+    /// 二分ヒープ
+    #[default]
+    BinaryHeap,
+    /// 距離ごとのバケット（Dialの方法）
+    Bucket,
+}
+
+impl fmt::Display for QueueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueueKind::BinaryHeap => write!(f, "heap"),
+            QueueKind::Bucket => write!(f, "bucket"),
+        }
+    }
+}
+
+impl FromStr for QueueKind {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        match spec {
+            "heap" => Ok(QueueKind::BinaryHeap),
+            "bucket" => Ok(QueueKind::Bucket),
+            _ => Err(format!(
+                "unknown queue `{}` (expected heap or bucket)",
+                spec
+            )),
+        }
+    }
+}
+
+/// 探索で未確定のノードを評価の良い順に取り出すキュー
+///
+/// 評価が同じならノード番号の小さい順に取り出す。
+pub(super) trait Queue<K> {
+    fn push(&mut self, key: K, node: usize);
+    fn pop(&mut self) -> Option<(K, usize)>;
+}
+
+/// 二分ヒープによるキュー
+pub(super) struct HeapQueue<K>(BinaryHeap<Reverse<(K, usize)>>);
+
+impl<K: Ord> HeapQueue<K> {
+    pub(super) fn new() -> Self {
+        HeapQueue(BinaryHeap::new())
+    }
+}
+
+impl<K: Ord> Queue<K> for HeapQueue<K> {
+    fn push(&mut self, key: K, node: usize) {
+        self.0.push(Reverse((key, node)));
+    }
+
+    fn pop(&mut self) -> Option<(K, usize)> {
+        self.0.pop().map(|Reverse(entry)| entry)
+    }
+}
+
+/// 距離ごとのバケットを循環させるキュー
+///
+/// 取り出した距離を`d`、リンクの距離の最大値を`C`とすると、以後に入る距離は
+/// `d`以上`d + C`以下なので、`C + 1`個のバケットで足りる。
+pub(super) struct BucketQueue {
+    /// 同じ距離のノード（小さい番号から取り出す）
+    buckets: Vec<BinaryHeap<Reverse<usize>>>,
+    /// `buckets[cursor]`の距離
+    base: isize,
+    cursor: usize,
+    len: usize,
+}
+
+impl BucketQueue {
+    /// リンクの距離の最大値が`max_distance`のグラフ用のキュー
+    pub(super) fn new(max_distance: isize) -> Self {
+        let size = usize::try_from(max_distance).expect("non-negative distance") + 1;
+        BucketQueue {
+            buckets: vec![BinaryHeap::new(); size],
+            base: 0,
+            cursor: 0,
+            len: 0,
+        }
+    }
+}
+
+impl Queue<isize> for BucketQueue {
+    fn push(&mut self, key: isize, node: usize) {
+        let offset = (key - self.base) as usize;
+        debug_assert!(offset < self.buckets.len());
+        let i = (self.cursor + offset) % self.buckets.len();
+        self.buckets[i].push(Reverse(node));
+        self.len += 1;
+    }
+
+    fn pop(&mut self) -> Option<(isize, usize)> {
+        if self.len == 0 {
+            return None;
+        }
+        loop {
+            if let Some(Reverse(node)) = self.buckets[self.cursor].pop() {
+                self.len -= 1;
+                return Some((self.base, node));
+            }
+            self.cursor = (self.cursor + 1) % self.buckets.len();
+            self.base += 1;
+        }
+    }
+}
+
+/// 始点からの経路の木（各ノードの評価と前ノード）
+pub(super) struct Tree<L> {
+    pub(super) source: usize,
+    /// 評価（到達していなければ`None`）
+    pub(super) labels: Vec<Option<L>>,
+    /// 前ノード表
+    pub(super) prev: Vec<usize>,
+}
+
+impl<L> Tree<L> {
+    /// `destination`への経路。到達していなければ`None`
+    pub(super) fn path(&self, destination: usize) -> Option<Vec<usize>> {
+        self.labels[destination].as_ref()?;
+        Some(trace_path(&self.prev, self.source, destination))
+    }
+}
+
+/// 隣接リストとキューによるDijkstra法
+///
+/// 評価`L`の経路を`extend`でリンクの先へ延ばし（使えないリンクなら`None`）、
+/// `key`の小さいものを良い評価とする。未確定のうち評価が最良のノード
+/// （同じならノード番号の小さいもの）から確定させ、前ノードはより良い評価を
+/// 与えるリンクが見つかったときだけ更新する。`destination`を与えるとその
+/// ノードを確定した時点で打ち切る。打ち切っても確定済みのノードの前ノードは
+/// 変わらないので、どの終点への経路も木全体を求めた場合と同じになる。
+pub(super) fn search<L: Copy, K: Ord + Copy>(
+    topology: &Topology,
+    source: usize,
+    destination: Option<usize>,
+    start: L,
+    extend: impl Fn(&L, &Adjacent) -> Option<L>,
+    key: impl Fn(&L) -> K,
+    queue: &mut impl Queue<K>,
+) -> Tree<L> {
+    let node_num = topology.node_num();
+    let mut labels: Vec<Option<L>> = vec![None; node_num];
+    let mut confirmed = vec![false; node_num];
+    let mut prev = vec![node_num; node_num];
+
+    labels[source] = Some(start);
+    prev[source] = source;
+    queue.push(key(&start), source);
+
+    // 評価が良くなったノードは重ねて入れ、古いものは確定済みとして読み飛ばす
+    while let Some((_, current_node)) = queue.pop() {
+        if confirmed[current_node] {
+            continue;
+        }
+        confirmed[current_node] = true;
+        if Some(current_node) == destination {
+            break;
+        }
+        let label = labels[current_node].expect("queued node has a label");
+
+        for adjacent in topology.neighbors(current_node) {
+            let i = adjacent.node;
+            if confirmed[i] {
+                continue;
+            }
+            let Some(candidate) = extend(&label, adjacent) else {
+                continue;
+            };
+            let candidate_key = key(&candidate);
+            if labels[i].is_none_or(|old| candidate_key < key(&old)) {
+                labels[i] = Some(candidate);
+                prev[i] = current_node;
+                queue.push(candidate_key, i);
+            }
+        }
+    }
+
+    Tree {
+        source,
+        labels,
+        prev,
+    }
+}
+
+/// `usable`なリンクだけを使う最短距離の木
+pub(super) fn shortest_tree(
+    topology: &Topology,
+    source: usize,
+    destination: Option<usize>,
+    usable: impl Fn(&Adjacent) -> bool,
+    queue: QueueKind,
+) -> Tree<isize> {
+    let extend = |distance: &isize, adjacent: &Adjacent| {
+        usable(adjacent).then(|| distance + topology.links()[adjacent.link].distance)
+    };
+    // バケットの数がリンク数を超えるならbucketでも二分ヒープを使う
+    let max_distance = (queue == QueueKind::Bucket)
+        .then(|| {
+            topology
+                .links()
+                .iter()
+                .map(|link| link.distance)
+                .max()
+                .unwrap_or(0)
+        })
+        .filter(|&max_distance| max_distance <= topology.links().len() as isize);
+    match max_distance {
+        Some(max_distance) => search(
+            topology,
+            source,
+            destination,
+            0,
+            extend,
+            |&distance| distance,
+            &mut BucketQueue::new(max_distance),
+        ),
+        None => search(
+            topology,
+            source,
+            destination,
+            0,
+            extend,
+            |&distance| distance,
+            &mut HeapQueue::new(),
+        ),
+    }
+}
+
+/// 前ノード表から始点→終点のノード列を復元する
+pub(super) fn trace_path(prev: &[usize], source: usize, destination: usize) -> Vec<usize> {
+    let mut path = vec![destination];
+    let mut node = destination;
+    while node != source {
+        node = prev[node];
+        path.push(node);
+    }
+    path.reverse();
+    path
+}
@@ -0,0 +1,119 @@
+use rand::{Rng, SeedableRng};
+
+use super::k_shortest_path::CandidatePaths;
+use super::Router;
+use crate::reservation::TrunkReservation;
+use crate::rng::SimRng;
+use crate::topology::Topology;
+
+/// Gibbens-KellyのDynamic Alternative Routing（DAR）
+///
+/// 呼はまず最短経路を試し、空きが無ければノード対ごとに覚えている
+/// 迂回経路（sticky alternate）を試す。呼損になると迂回経路を候補から
+/// ランダムに選び直し、呼が通る限り同じ迂回経路を使い続ける。
+/// 候補はYenのアルゴリズムで求めたK本の最短経路のうち2本目以降。
+/// 迂回経路は、ノード対で初めて迂回が必要になったときに選ぶ。
+#[derive(Clone)]
+pub struct DynamicAlternative {
+    node_num: usize,
+    candidates: CandidatePaths,
+    /// ノード対ごとの現在の迂回経路（候補番号、`source * node_num + destination`）。
+    /// まだ選んでいなければ`None`、迂回経路が無ければ0
+    sticky: Vec<Option<usize>>,
+    rng: SimRng,
+}
+
+impl DynamicAlternative {
+    /// ノード対ごとに最大`k`本の候補経路（最短経路と`k - 1`本の迂回経路）を求める
+    pub fn new(topology: &Topology, k: usize) -> Self {
+        let node_num = topology.node_num();
+        DynamicAlternative {
+            node_num,
+            candidates: CandidatePaths::new(topology, k),
+            sticky: vec![None; node_num * node_num],
+            rng: SimRng::seed_from_u64(0),
+        }
+    }
+
+    /// 候補を求めたノード対の数（方式の複製と共有する表で数える）
+    pub fn computed_pairs(&self) -> usize {
+        self.candidates.computed_pairs()
+    }
+
+    /// 最短経路が`admits`を満たせば最短経路、満たさなければ迂回経路が満たす場合に迂回経路
+    fn route(
+        &mut self,
+        source: usize,
+        destination: usize,
+        admits: impl Fn(&[usize]) -> bool,
+    ) -> Option<Vec<usize>> {
+        let candidates = self.candidates.get(source, destination);
+        if candidates.paths.is_empty() {
+            return None;
+        }
+        if admits(&candidates.links[0]) {
+            return Some(candidates.paths[0].clone());
+        }
+        let sticky = match self.sticky[source * self.node_num + destination] {
+            Some(sticky) => sticky,
+            None => self.redraw(source, destination),
+        };
+        let candidates = self.candidates.get(source, destination);
+        if sticky > 0 && admits(&candidates.links[sticky]) {
+            return Some(candidates.paths[sticky].clone());
+        }
+        None
+    }
+
+    /// ノード対の迂回経路をランダムに選び直す
+    fn redraw(&mut self, source: usize, destination: usize) -> usize {
+        let count = self.candidates.get(source, destination).paths.len();
+        let sticky = if count > 1 {
+            self.rng.gen_range(1..count)
+        } else {
+            0
+        };
+        self.sticky[source * self.node_num + destination] = Some(sticky);
+        sticky
+    }
+}
+
+impl Router for DynamicAlternative {
+    fn name(&self) -> &'static str {
+        "dynamic-alternative"
+    }
+
+    fn find_path(
+        &mut self,
+        source: usize,
+        destination: usize,
+        bandwidth: &[isize],
+        demand: isize,
+    ) -> Option<Vec<usize>> {
+        self.route(source, destination, |links| {
+            links.iter().all(|&link| bandwidth[link] >= demand)
+        })
+    }
+
+    fn find_reserved_path(
+        &mut self,
+        source: usize,
+        destination: usize,
+        bandwidth: &[isize],
+        demand: isize,
+        reservation: &TrunkReservation,
+    ) -> Option<Vec<usize>> {
+        self.route(source, destination, |links| {
+            reservation.admits(source, destination, links, bandwidth, demand)
+        })
+    }
+
+    fn reset(&mut self, seed: u64) {
+        self.rng = SimRng::seed_from_u64(seed);
+        self.sticky.fill(None);
+    }
+
+    fn blocked(&mut self, source: usize, destination: usize) {
+        self.redraw(source, destination);
+    }
+}
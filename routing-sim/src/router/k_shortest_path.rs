@@ -0,0 +1,240 @@
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
+
+use super::dijkstra::{shortest_tree, QueueKind};
+use super::Router;
+use crate::reservation::TrunkReservation;
+use crate::topology::Topology;
+
+/// 1つのノード対の経路の候補（距離の短い順）
+#[derive(Clone, Debug, Default)]
+pub(super) struct Candidates {
+    pub(super) paths: Vec<Vec<usize>>,
+    /// 各候補の経路上のリンク
+    pub(super) links: Vec<Vec<usize>>,
+}
+
+/// ノード対ごとの経路の候補
+///
+/// 候補は空き容量によらないので、初めて呼が発生したノード対について
+/// Yenのアルゴリズムで求め、以後はそれを使い続ける。表は方式の複製と
+/// 共有するので、実行ごとに複製した方式でも各ノード対を1回しか求めない。
+#[derive(Clone)]
+pub(super) struct CandidatePaths(Arc<CandidateTable>);
+
+struct CandidateTable {
+    topology: Topology,
+    k: usize,
+    /// ノード対の候補（`source * node_num + destination`、求めるまでは空）
+    pairs: Vec<OnceLock<Candidates>>,
+    /// 候補を求めたノード対の数
+    computed: AtomicUsize,
+}
+
+impl CandidatePaths {
+    /// ノード対ごとに最大`k`本の経路を候補にする
+    pub(super) fn new(topology: &Topology, k: usize) -> Self {
+        let node_num = topology.node_num();
+        CandidatePaths(Arc::new(CandidateTable {
+            topology: topology.clone(),
+            k,
+            pairs: (0..node_num * node_num).map(|_| OnceLock::new()).collect(),
+            computed: AtomicUsize::new(0),
+        }))
+    }
+
+    /// ノード対の候補（まだ求めていなければ求める）
+    pub(super) fn get(&self, source: usize, destination: usize) -> &Candidates {
+        let table = &*self.0;
+        let topology = &table.topology;
+        table.pairs[source * topology.node_num() + destination].get_or_init(|| {
+            table.computed.fetch_add(1, Ordering::Relaxed);
+            let paths = if source == destination {
+                Vec::new()
+            } else {
+                k_shortest_paths(topology, source, destination, table.k)
+            };
+            let links = paths
+                .iter()
+                .map(|path| topology.path_links(path).expect("path follows links"))
+                .collect();
+            Candidates { paths, links }
+        })
+    }
+
+    /// 候補を求めたノード対の数（複製と共有する表全体で数える）
+    pub(super) fn computed_pairs(&self) -> usize {
+        self.0.computed.load(Ordering::Relaxed)
+    }
+}
+
+/// Yenのアルゴリズムで求めたK本のループの無い最短経路を
+/// 短い順に試す固定代替経路方式
+#[derive(Clone)]
+pub struct KShortestPath {
+    candidates: CandidatePaths,
+}
+
+impl KShortestPath {
+    /// ノード対ごとに最大`k`本の経路を求める
+    pub fn new(topology: &Topology, k: usize) -> Self {
+        KShortestPath {
+            candidates: CandidatePaths::new(topology, k),
+        }
+    }
+
+    /// 候補を求めたノード対の数（方式の複製と共有する表で数える）
+    pub fn computed_pairs(&self) -> usize {
+        self.candidates.computed_pairs()
+    }
+
+    /// 経路上のリンクが`admits`を満たす最初の候補
+    fn first_admitted(
+        &self,
+        source: usize,
+        destination: usize,
+        admits: impl Fn(&[usize]) -> bool,
+    ) -> Option<Vec<usize>> {
+        let candidates = self.candidates.get(source, destination);
+        candidates
+            .links
+            .iter()
+            .position(|links| admits(links))
+            .map(|i| candidates.paths[i].clone())
+    }
+}
+
+impl Router for KShortestPath {
+    fn name(&self) -> &'static str {
+        "k-shortest-path"
+    }
+
+    fn find_path(
+        &mut self,
+        source: usize,
+        destination: usize,
+        bandwidth: &[isize],
+        demand: isize,
+    ) -> Option<Vec<usize>> {
+        // 呼の帯域分の空き容量がある最初の候補を使う
+        self.first_admitted(source, destination, |links| {
+            links.iter().all(|&link| bandwidth[link] >= demand)
+        })
+    }
+
+    fn find_reserved_path(
+        &mut self,
+        source: usize,
+        destination: usize,
+        bandwidth: &[isize],
+        demand: isize,
+        reservation: &TrunkReservation,
+    ) -> Option<Vec<usize>> {
+        // 予約の条件を満たす最初の候補を使う
+        self.first_admitted(source, destination, |links| {
+            reservation.admits(source, destination, links, bandwidth, demand)
+        })
+    }
+}
+
+/// Yenのアルゴリズムで`source`から`destination`への距離の短い順に
+/// 最大`k`本のループの無い経路を求める
+///
+/// 1本目は最短経路の探索の規則（[`WidthMode`](super::WidthMode)を参照）で決まる。
+/// 2本目以降は、それまでに見つかった候補のうち距離が最短のもの
+/// （同じならノード列の辞書順で最初のもの）を順に選ぶ。
+pub fn k_shortest_paths(
+    topology: &Topology,
+    source: usize,
+    destination: usize,
+    k: usize,
+) -> Vec<Vec<usize>> {
+    let node_num = topology.node_num();
+    let link_num = topology.links().len();
+    let mut removed_links = vec![false; link_num];
+    let mut removed_nodes = vec![false; node_num];
+
+    let mut paths: Vec<Vec<usize>> = Vec::new();
+    match shortest_path(
+        topology,
+        source,
+        destination,
+        &removed_links,
+        &removed_nodes,
+    ) {
+        Some((_, path)) => paths.push(path),
+        None => return paths,
+    }
+    // 候補の経路（距離, ノード列）
+    let mut candidates: BTreeSet<(isize, Vec<usize>)> = BTreeSet::new();
+
+    while paths.len() < k {
+        let previous = paths.last().expect("at least one path").clone();
+        // 前の経路の各ノードから分岐する経路を探す
+        for i in 0..previous.len() - 1 {
+            let spur_node = previous[i];
+            let root = &previous[..=i];
+
+            // 同じ根を持つ既知の経路の次のリンクを除く
+            for path in &paths {
+                if path.len() > i + 1 && &path[..=i] == root {
+                    if let Some(link) = topology.link_between(path[i], path[i + 1]) {
+                        removed_links[link] = true;
+                    }
+                }
+            }
+            // ループにならないように根のノードを除く
+            for &node in &root[..i] {
+                removed_nodes[node] = true;
+            }
+
+            if let Some((spur_distance, spur)) = shortest_path(
+                topology,
+                spur_node,
+                destination,
+                &removed_links,
+                &removed_nodes,
+            ) {
+                let mut path = root[..i].to_vec();
+                path.extend(spur);
+                let distance = topology.path_distance(root) + spur_distance;
+                if !paths.contains(&path) {
+                    candidates.insert((distance, path));
+                }
+            }
+
+            removed_links
+                .iter_mut()
+                .for_each(|removed| *removed = false);
+            removed_nodes
+                .iter_mut()
+                .for_each(|removed| *removed = false);
+        }
+
+        match candidates.pop_first() {
+            Some((_, path)) => paths.push(path),
+            None => break,
+        }
+    }
+
+    paths
+}
+
+/// 除いたリンク・ノードを使わない最短経路を求める（距離とノード列）
+fn shortest_path(
+    topology: &Topology,
+    source: usize,
+    destination: usize,
+    removed_links: &[bool],
+    removed_nodes: &[bool],
+) -> Option<(isize, Vec<usize>)> {
+    let tree = shortest_tree(
+        topology,
+        source,
+        Some(destination),
+        |adjacent| !removed_nodes[adjacent.node] && !removed_links[adjacent.link],
+        QueueKind::BinaryHeap,
+    );
+    Some((tree.labels[destination]?, tree.path(destination)?))
+}
@@ -0,0 +1,92 @@
+use super::k_shortest_path::CandidatePaths;
+use super::Router;
+use crate::reservation::TrunkReservation;
+use crate::topology::{Topology, MAX};
+
+/// 候補経路のうち、ボトルネックの空き容量が最大の経路を選ぶ方式
+///
+/// 候補はノード対ごとにYenのアルゴリズムで求めたK本の最短経路。
+/// 空き容量が同じならホップ数の少ない経路、それも同じなら距離の短い経路を選ぶ。
+#[derive(Clone)]
+pub struct LeastLoaded {
+    candidates: CandidatePaths,
+}
+
+impl LeastLoaded {
+    /// ノード対ごとに最大`k`本の候補経路を求める
+    pub fn new(topology: &Topology, k: usize) -> Self {
+        LeastLoaded {
+            candidates: CandidatePaths::new(topology, k),
+        }
+    }
+
+    /// 候補を求めたノード対の数（方式の複製と共有する表で数える）
+    pub fn computed_pairs(&self) -> usize {
+        self.candidates.computed_pairs()
+    }
+
+    /// 経路上のリンクが`admits`を満たす候補のうち、ボトルネックの空き容量が最大のもの
+    fn least_loaded(
+        &self,
+        source: usize,
+        destination: usize,
+        bandwidth: &[isize],
+        admits: impl Fn(&[usize]) -> bool,
+    ) -> Option<Vec<usize>> {
+        // (ボトルネックの空き容量, ホップ数, 候補番号)
+        let mut best: Option<(isize, usize, usize)> = None;
+        let candidates = self.candidates.get(source, destination);
+        for (i, links) in candidates.links.iter().enumerate() {
+            if !admits(links) {
+                continue;
+            }
+            let bottleneck = links
+                .iter()
+                .map(|&link| bandwidth[link])
+                .min()
+                .unwrap_or(MAX);
+            let better = match best {
+                None => true,
+                Some((best_bottleneck, best_hops, _)) => {
+                    bottleneck > best_bottleneck
+                        || (bottleneck == best_bottleneck && links.len() < best_hops)
+                }
+            };
+            if better {
+                best = Some((bottleneck, links.len(), i));
+            }
+        }
+        best.map(|(_, _, i)| candidates.paths[i].clone())
+    }
+}
+
+impl Router for LeastLoaded {
+    fn name(&self) -> &'static str {
+        "least-loaded"
+    }
+
+    fn find_path(
+        &mut self,
+        source: usize,
+        destination: usize,
+        bandwidth: &[isize],
+        demand: isize,
+    ) -> Option<Vec<usize>> {
+        self.least_loaded(source, destination, bandwidth, |links| {
+            links.iter().all(|&link| bandwidth[link] >= demand)
+        })
+    }
+
+    fn find_reserved_path(
+        &mut self,
+        source: usize,
+        destination: usize,
+        bandwidth: &[isize],
+        demand: isize,
+        reservation: &TrunkReservation,
+    ) -> Option<Vec<usize>> {
+        self.least_loaded(source, destination, bandwidth, |links| {
+            reservation.admits(source, destination, links, bandwidth, demand)
+        })
+    }
+}
@@ -0,0 +1,44 @@
+use super::width::{PathTrees, WidthMode};
+use super::Router;
+use crate::topology::Topology;
+
+/// リンク容量の大きい経路を事前計算しておく固定経路方式
+///
+/// 経路は空のネットワークのリンク容量について [`WidthMode`] の規則で選ぶ。
+/// 始点ごとに全終点への経路を前ノード表の木として1度に求めて保持するので、
+/// 事前計算は始点あたり数回のDijkstra法で済み、記憶量もノード数の2乗に比例する。
+#[derive(Clone)]
+pub struct MaximumShortestPath {
+    /// 始点ごとの経路の木
+    trees: Vec<PathTrees>,
+}
+
+impl MaximumShortestPath {
+    pub fn new(topology: &Topology, mode: WidthMode) -> Self {
+        let capacity = topology.capacities();
+        let trees = (0..topology.node_num())
+            .map(|source| mode.trees(topology, &capacity, 1, source))
+            .collect();
+        MaximumShortestPath { trees }
+    }
+}
+
+impl Router for MaximumShortestPath {
+    fn name(&self) -> &'static str {
+        "maximum-shortest-path"
+    }
+
+    fn find_path(
+        &mut self,
+        source: usize,
+        destination: usize,
+        _bandwidth: &[isize],
+        _demand: isize,
+    ) -> Option<Vec<usize>> {
+        self.trees[source].path(destination)
+    }
+
+    fn is_fixed(&self) -> bool {
+        true
+    }
+}
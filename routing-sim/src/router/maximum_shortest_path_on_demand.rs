@@ -0,0 +1,44 @@
+use super::route_cache::RouteCache;
+use super::width::WidthMode;
+use super::Router;
+use crate::topology::Topology;
+
+/// 呼ごとにリンクの空き容量について [`WidthMode`] の規則で経路を探索する方式
+///
+/// 求めた経路はノード対ごとにキャッシュし、空き容量の変化で経路が変わりうる
+/// ときだけ探索し直す。
+#[derive(Clone)]
+pub struct MaximumShortestPathOnDemand {
+    topology: Topology,
+    cache: RouteCache,
+}
+
+impl MaximumShortestPathOnDemand {
+    pub fn new(topology: &Topology, mode: WidthMode) -> Self {
+        MaximumShortestPathOnDemand {
+            topology: topology.clone(),
+            cache: RouteCache::new(topology, mode),
+        }
+    }
+}
+
+impl Router for MaximumShortestPathOnDemand {
+    fn name(&self) -> &'static str {
+        "maximum-shortest-path-on-demand"
+    }
+
+    fn find_path(
+        &mut self,
+        source: usize,
+        destination: usize,
+        bandwidth: &[isize],
+        demand: isize,
+    ) -> Option<Vec<usize>> {
+        self.cache
+            .find_path(&self.topology, bandwidth, demand, source, destination)
+    }
+
+    fn reset(&mut self, _seed: u64) {
+        self.cache.clear(&self.topology);
+    }
+}
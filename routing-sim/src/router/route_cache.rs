@@ -0,0 +1,170 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::width::{path_width, WidthMode};
+use crate::topology::Topology;
+
+/// キャッシュのキー（始点, 終点, 呼の帯域）
+type Key = (usize, usize, isize);
+
+/// 求めた経路と、それが変わりうる条件
+#[derive(Clone)]
+struct Entry {
+    path: Option<Vec<usize>>,
+    /// 経路上のリンク
+    links: Vec<usize>,
+    /// 無効化の判定に使う幅（[`RouteCache::sync`]を参照）
+    threshold: isize,
+}
+
+/// [`WidthMode::find_path`] の結果をノード対と帯域ごとに覚えておくキャッシュ
+///
+/// 空き容量の変化で結果が変わりうるエントリだけを捨てるので、
+/// 常に [`WidthMode::find_path`] を直接呼んだ場合と同じ経路を返す。
+#[derive(Clone)]
+pub(super) struct RouteCache {
+    mode: WidthMode,
+    /// 最後に見た空き容量
+    bandwidth: Vec<isize>,
+    entries: HashMap<Key, Entry>,
+    /// リンクごとの、そのリンクを経路に含むエントリ
+    by_link: Vec<HashSet<Key>>,
+    /// 無効化の判定に使う幅ごとのエントリ
+    by_threshold: BTreeMap<isize, HashSet<Key>>,
+    /// 呼の帯域ごとのエントリ
+    by_demand: BTreeMap<isize, HashSet<Key>>,
+}
+
+impl RouteCache {
+    pub(super) fn new(topology: &Topology, mode: WidthMode) -> Self {
+        RouteCache {
+            mode,
+            bandwidth: topology.capacities(),
+            entries: HashMap::new(),
+            by_link: vec![HashSet::new(); topology.links().len()],
+            by_threshold: BTreeMap::new(),
+            by_demand: BTreeMap::new(),
+        }
+    }
+
+    /// 空き容量`bandwidth`のときの経路
+    pub(super) fn find_path(
+        &mut self,
+        topology: &Topology,
+        bandwidth: &[isize],
+        demand: isize,
+        source: usize,
+        destination: usize,
+    ) -> Option<Vec<usize>> {
+        self.sync(bandwidth);
+        let key = (source, destination, demand);
+        if let Some(entry) = self.entries.get(&key) {
+            return entry.path.clone();
+        }
+
+        let path = self
+            .mode
+            .find_path(topology, bandwidth, demand, source, destination);
+        let (links, threshold) = match &path {
+            Some(path) => {
+                let width = path_width(topology, bandwidth, path);
+                (
+                    topology.path_links(path).expect("path follows links"),
+                    width,
+                )
+            }
+            None => (Vec::new(), demand),
+        };
+        // shortest-widestの経路の無いエントリは、帯域以上になったリンクで無効にする
+        let threshold = match (self.mode, &path) {
+            (WidthMode::ShortestWidest, None) => demand - 1,
+            _ => threshold,
+        };
+        for &link in &links {
+            self.by_link[link].insert(key);
+        }
+        self.by_threshold.entry(threshold).or_default().insert(key);
+        self.by_demand.entry(demand).or_default().insert(key);
+        self.entries.insert(
+            key,
+            Entry {
+                path: path.clone(),
+                links,
+                threshold,
+            },
+        );
+        path
+    }
+
+    /// すべてのエントリを捨てる
+    pub(super) fn clear(&mut self, topology: &Topology) {
+        *self = RouteCache::new(topology, self.mode);
+    }
+
+    /// 前回から変化したリンクについて、結果が変わりうるエントリを捨てる
+    ///
+    /// エントリの経路の幅を`B`、呼の帯域を`d`とする。
+    ///
+    /// - shortest-widest：結果は幅`B`以上のリンクの集合だけで決まり、
+    ///   それが変わるのは容量が`B`をまたぐか、`B + 1`以上になってより広い
+    ///   経路ができる場合。増加は`old <= B <= new`、減少は経路上のリンクが
+    ///   `B`未満になる場合に捨てる。経路の無いエントリは`B = d - 1`とする。
+    /// - widest-shortest・widest：増加は`new >= B`（同じ評価以上の経路ができうる）
+    ///   か、使えなかったリンクが使えるようになる場合（`old < d`）に捨てる。
+    ///   減少は経路上のリンクの場合だけ捨てる。経路の外のリンクが減っても、
+    ///   経路上のノードの評価は変わらず、競合する候補が悪くなるだけなので
+    ///   探索の結果は変わらない。経路の無いエントリは`B = d`とする。
+    fn sync(&mut self, bandwidth: &[isize]) {
+        let mut stale: Vec<Key> = Vec::new();
+        for (link, (&new, old)) in bandwidth.iter().zip(self.bandwidth.iter_mut()).enumerate() {
+            let old = std::mem::replace(old, new);
+            if new < old {
+                for key in &self.by_link[link] {
+                    let entry = &self.entries[key];
+                    if self.mode != WidthMode::ShortestWidest || new < entry.threshold {
+                        stale.push(*key);
+                    }
+                }
+            } else if new > old {
+                match self.mode {
+                    WidthMode::ShortestWidest => {
+                        for keys in self.by_threshold.range(old..=new).map(|(_, keys)| keys) {
+                            stale.extend(keys);
+                        }
+                    }
+                    WidthMode::WidestShortest | WidthMode::Widest => {
+                        for keys in self.by_threshold.range(..=new).map(|(_, keys)| keys) {
+                            stale.extend(keys);
+                        }
+                        for keys in self.by_demand.range(old + 1..).map(|(_, keys)| keys) {
+                            stale.extend(keys);
+                        }
+                    }
+                }
+            }
+        }
+        for key in stale {
+            self.remove(&key);
+        }
+    }
+
+    fn remove(&mut self, key: &Key) {
+        let Some(entry) = self.entries.remove(key) else {
+            return;
+        };
+        for link in entry.links {
+            self.by_link[link].remove(key);
+        }
+        if let Some(keys) = self.by_threshold.get_mut(&entry.threshold) {
+            keys.remove(key);
+            if keys.is_empty() {
+                self.by_threshold.remove(&entry.threshold);
+            }
+        }
+        if let Some(keys) = self.by_demand.get_mut(&key.2) {
+            keys.remove(key);
+            if keys.is_empty() {
+                self.by_demand.remove(&key.2);
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use super::Router;
+use crate::topology::{Topology, MAX};
+
+/// Floyd-Warshallで事前計算した最短経路を使う固定経路方式
+#[derive(Clone)]
+pub struct ShortestPath {
+    node_num: usize,
+    /// 最短距離を格納する行列（`i * node_num + j`）
+    dist_matrix: Vec<isize>,
+    /// 経路を格納する行列（`i * node_num + j`）
+    next_node: Vec<usize>,
+}
+
+impl ShortestPath {
+    pub fn new(topology: &Topology) -> Self {
+        let node_num = topology.node_num();
+        let mut dist_matrix = vec![MAX; node_num * node_num];
+        let mut next_node = vec![node_num; node_num * node_num];
+        for i in 0..node_num {
+            dist_matrix[i * node_num + i] = 0;
+            next_node[i * node_num + i] = i;
+        }
+        for link in topology.links() {
+            let (u, v) = (link.node1, link.node2);
+            dist_matrix[u * node_num + v] = link.distance;
+            dist_matrix[v * node_num + u] = link.distance;
+            next_node[u * node_num + v] = v;
+            next_node[v * node_num + u] = u;
+        }
+
+        // Floyd-Warshallアルゴリズムによる全点間最短経路の計算
+        for k in 0..node_num {
+            for i in 0..node_num {
+                let dist_ik = dist_matrix[i * node_num + k];
+                if dist_ik == MAX {
+                    continue;
+                }
+                for j in 0..node_num {
+                    let dist_kj = dist_matrix[k * node_num + j];
+                    if dist_kj != MAX && dist_matrix[i * node_num + j] > dist_ik + dist_kj {
+                        dist_matrix[i * node_num + j] = dist_ik + dist_kj;
+                        next_node[i * node_num + j] = next_node[i * node_num + k];
+                    }
+                }
+            }
+        }
+
+        ShortestPath {
+            node_num,
+            dist_matrix,
+            next_node,
+        }
+    }
+
+    /// `source`から`destination`への最短距離。経路が無ければ`None`
+    pub fn distance(&self, source: usize, destination: usize) -> Option<isize> {
+        match self.dist_matrix[source * self.node_num + destination] {
+            MAX => None,
+            distance => Some(distance),
+        }
+    }
+}
+
+impl Router for ShortestPath {
+    fn name(&self) -> &'static str {
+        "shortest-path"
+    }
+
+    fn find_path(
+        &mut self,
+        source: usize,
+        destination: usize,
+        _bandwidth: &[isize],
+        _demand: isize,
+    ) -> Option<Vec<usize>> {
+        // 経路が存在しない場合
+        if self.dist_matrix[source * self.node_num + destination] == MAX {
+            return None;
+        }
+
+        let mut path = Vec::new();
+        let mut current = source;
+        while current != destination {
+            path.push(current);
+            current = self.next_node[current * self.node_num + destination];
+        }
+        path.push(destination);
+        Some(path)
+    }
+
+    fn is_fixed(&self) -> bool {
+        true
+    }
+}
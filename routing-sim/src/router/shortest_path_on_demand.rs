@@ -0,0 +1,42 @@
+use super::dijkstra::{shortest_tree, QueueKind};
+use super::Router;
+use crate::topology::Topology;
+
+/// 呼の帯域分の空き容量があるリンクだけを使って呼ごとにDijkstraで最短経路を求める方式
+#[derive(Clone)]
+pub struct ShortestPathOnDemand {
+    topology: Topology,
+    queue: QueueKind,
+}
+
+impl ShortestPathOnDemand {
+    pub fn new(topology: &Topology, queue: QueueKind) -> Self {
+        ShortestPathOnDemand {
+            topology: topology.clone(),
+            queue,
+        }
+    }
+}
+
+impl Router for ShortestPathOnDemand {
+    fn name(&self) -> &'static str {
+        "shortest-path-on-demand"
+    }
+
+    fn find_path(
+        &mut self,
+        source: usize,
+        destination: usize,
+        bandwidth: &[isize],
+        demand: isize,
+    ) -> Option<Vec<usize>> {
+        shortest_tree(
+            &self.topology,
+            source,
+            Some(destination),
+            |adjacent| bandwidth[adjacent.link] >= demand,
+            self.queue,
+        )
+        .path(destination)
+    }
+}
@@ -0,0 +1,273 @@
+use std::cmp::Reverse;
+use std::fmt;
+use std::str::FromStr;
+
+use super::dijkstra::{self, trace_path, HeapQueue, Tree};
+use crate::topology::{Adjacent, Topology};
+
+/// 経路の幅（ボトルネック容量）と距離のどちらを優先するか
+///
+/// 経路の距離はリンクの距離の和、幅は経路上のリンク容量の最小値。
+/// いずれのモードも容量が呼の帯域に満たないリンクは使わない。
+/// 同じ評価の経路が複数あるときは、次の規則で1本に決まる：
+/// 探索は未確定のノードのうち評価が最良のもの（同じならノード番号の小さいもの）
+/// から確定させ、前ノードはより良い評価を与えるリンクが見つかったときだけ更新する。
+///
+/// 文字列からは `widest-shortest`、`shortest-widest`、`widest` の形式で作る。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WidthMode {
+    /// 距離が最小の経路のうち幅が最大のもの
+    WidestShortest,
+    /// 幅が最大の経路のうち距離が最小のもの
+    ///
+    /// 従来のmaximum-shortest-pathの探索（容量の大きいリンクから順に加え、
+    /// 終点に到達したサブグラフ上の最短経路を選ぶ）と同じ経路になる。
+    #[default]
+    ShortestWidest,
+    /// 幅が最大の経路（距離は考えない）。修正Dijkstra法で求める
+    Widest,
+}
+
+impl WidthMode {
+    /// リンクの容量が`capacity`（添字はリンク番号）のとき、帯域`demand`の呼の
+    /// `source`から`destination`への経路を求める。経路が無ければ`None`
+    pub fn find_path(
+        &self,
+        topology: &Topology,
+        capacity: &[isize],
+        demand: isize,
+        source: usize,
+        destination: usize,
+    ) -> Option<Vec<usize>> {
+        let usable = |link: usize| capacity[link] >= demand;
+        match self {
+            WidthMode::WidestShortest => label_search(
+                topology,
+                capacity,
+                source,
+                destination,
+                usable,
+                shorter_then_wider,
+            ),
+            WidthMode::ShortestWidest => {
+                // 最大の幅を求め、その幅以上のリンクだけで最短経路を探す。
+                // (幅, 距離)の辞書式順序は経路の延長で保たれないため1回の探索では求まらない
+                let widest = label_search(topology, capacity, source, destination, usable, wider)?;
+                let width = path_width(topology, capacity, &widest);
+                label_search(
+                    topology,
+                    capacity,
+                    source,
+                    destination,
+                    |link| capacity[link] >= width,
+                    shorter,
+                )
+            }
+            WidthMode::Widest => {
+                label_search(topology, capacity, source, destination, usable, wider)
+            }
+        }
+    }
+
+    /// `source`から全ノードへの経路を [`WidthMode::find_path`] と同じ規則で求める
+    ///
+    /// widest-shortest・widestは1本の木になる。shortest-widestは終点ごとに
+    /// 最大の幅が異なるので、終点の最大の幅の種類ごとに1本ずつ木を作る。
+    pub fn trees(
+        &self,
+        topology: &Topology,
+        capacity: &[isize],
+        demand: isize,
+        source: usize,
+    ) -> PathTrees {
+        let node_num = topology.node_num();
+        let usable = |link: usize| capacity[link] >= demand;
+        let single = |tree: Tree<Label>| PathTrees {
+            source,
+            tree_of: tree
+                .labels
+                .iter()
+                .map(|label| label.map_or(UNREACHABLE, |_| 0))
+                .collect(),
+            trees: vec![tree.prev],
+        };
+        match self {
+            WidthMode::WidestShortest => single(label_tree(
+                topology,
+                capacity,
+                source,
+                None,
+                usable,
+                shorter_then_wider,
+            )),
+            WidthMode::Widest => {
+                single(label_tree(topology, capacity, source, None, usable, wider))
+            }
+            WidthMode::ShortestWidest => {
+                let labels = label_tree(topology, capacity, source, None, usable, wider).labels;
+                // 終点の最大の幅ごとに、その幅以上のリンクだけの最短経路木を作る
+                let mut widths: Vec<isize> = labels
+                    .iter()
+                    .enumerate()
+                    .filter(|&(node, _)| node != source)
+                    .filter_map(|(_, label)| label.map(|label| label.width))
+                    .collect();
+                widths.sort_unstable();
+                widths.dedup();
+
+                let mut tree_of = vec![UNREACHABLE; node_num];
+                tree_of[source] = 0;
+                let mut trees = Vec::with_capacity(widths.len());
+                for (i, &width) in widths.iter().enumerate() {
+                    let prev = label_tree(
+                        topology,
+                        capacity,
+                        source,
+                        None,
+                        |link| capacity[link] >= width,
+                        shorter,
+                    )
+                    .prev;
+                    trees.push(prev);
+                    for (node, label) in labels.iter().enumerate() {
+                        if node != source && label.is_some_and(|label| label.width == width) {
+                            tree_of[node] = i;
+                        }
+                    }
+                }
+                if trees.is_empty() {
+                    trees.push(vec![node_num; node_num]);
+                }
+                PathTrees {
+                    source,
+                    tree_of,
+                    trees,
+                }
+            }
+        }
+    }
+}
+
+/// 距離が短い。同じなら幅が広い
+fn shorter_then_wider(label: &Label) -> (isize, Reverse<isize>) {
+    (label.distance, Reverse(label.width))
+}
+
+/// 距離が短い
+fn shorter(label: &Label) -> isize {
+    label.distance
+}
+
+/// 幅が広い
+fn wider(label: &Label) -> Reverse<isize> {
+    Reverse(label.width)
+}
+
+/// 到達できないノードの [`PathTrees`] の木の番号
+const UNREACHABLE: usize = usize::MAX;
+
+/// 1つの始点から全ノードへの経路（前ノード表の木）
+#[derive(Clone, Debug)]
+pub struct PathTrees {
+    source: usize,
+    /// 終点ごとに使う木の番号（到達できなければ`UNREACHABLE`）
+    tree_of: Vec<usize>,
+    /// 前ノード表
+    trees: Vec<Vec<usize>>,
+}
+
+impl PathTrees {
+    /// `destination`への経路。到達できなければ`None`
+    pub fn path(&self, destination: usize) -> Option<Vec<usize>> {
+        match self.tree_of[destination] {
+            UNREACHABLE => None,
+            tree => Some(trace_path(&self.trees[tree], self.source, destination)),
+        }
+    }
+}
+
+impl fmt::Display for WidthMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WidthMode::WidestShortest => write!(f, "widest-shortest"),
+            WidthMode::ShortestWidest => write!(f, "shortest-widest"),
+            WidthMode::Widest => write!(f, "widest"),
+        }
+    }
+}
+
+impl FromStr for WidthMode {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        match spec {
+            "widest-shortest" => Ok(WidthMode::WidestShortest),
+            "shortest-widest" => Ok(WidthMode::ShortestWidest),
+            "widest" => Ok(WidthMode::Widest),
+            _ => Err(format!(
+                "unknown width mode `{}` (expected widest-shortest, shortest-widest or widest)",
+                spec
+            )),
+        }
+    }
+}
+
+/// 経路の幅（経路上のリンク容量の最小値）
+pub fn path_width(topology: &Topology, capacity: &[isize], path: &[usize]) -> isize {
+    topology
+        .path_links(path)
+        .expect("path follows links")
+        .iter()
+        .map(|&link| capacity[link])
+        .min()
+        .unwrap_or(isize::MAX)
+}
+
+/// 始点からの経路の評価
+#[derive(Clone, Copy, Debug)]
+struct Label {
+    distance: isize,
+    width: isize,
+}
+
+/// `key`の小さい経路を良いものとして`source`から`destination`への経路を探す
+fn label_search<K: Ord + Copy>(
+    topology: &Topology,
+    capacity: &[isize],
+    source: usize,
+    destination: usize,
+    usable: impl Fn(usize) -> bool,
+    key: impl Fn(&Label) -> K,
+) -> Option<Vec<usize>> {
+    label_tree(topology, capacity, source, Some(destination), usable, key).path(destination)
+}
+
+/// `source`からの経路の木（各ノードの評価と前ノード）を求める
+fn label_tree<K: Ord + Copy>(
+    topology: &Topology,
+    capacity: &[isize],
+    source: usize,
+    destination: Option<usize>,
+    usable: impl Fn(usize) -> bool,
+    key: impl Fn(&Label) -> K,
+) -> Tree<Label> {
+    let start = Label {
+        distance: 0,
+        width: isize::MAX,
+    };
+    let extend = |label: &Label, adjacent: &Adjacent| {
+        usable(adjacent.link).then(|| Label {
+            distance: label.distance + topology.links()[adjacent.link].distance,
+            width: label.width.min(capacity[adjacent.link]),
+        })
+    };
+    dijkstra::search(
+        topology,
+        source,
+        destination,
+        start,
+        extend,
+        key,
+        &mut HeapQueue::new(),
+    )
+}
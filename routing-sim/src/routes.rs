@@ -0,0 +1,137 @@
+//! 確立できた呼の経路の長さの分布
+//!
+//! 方式が選んだ経路のホップ数・総距離と、Floyd-Warshallで求めた最短距離に
+//! 対する総距離の比（伸長率）を全実行で合計して度数分布にする。
+//! 迂回の多い方式がどれだけ余分にリンクを使っているかを見るために使う。
+
+use std::collections::BTreeMap;
+
+use crate::router::ShortestPath;
+use crate::simulator::RunResult;
+use crate::topology::Topology;
+
+/// 伸長率の度数分布の階級の数（伸長率1あたり）
+///
+/// 伸長率`r`の呼は`[1 + k / 10, 1 + (k + 1) / 10)`の階級`k`に数える。
+pub const STRETCH_BINS_PER_UNIT: isize = 10;
+
+/// 全実行で合計した、確立できた呼の経路の統計
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RouteStats {
+    /// 確立できた呼の数
+    calls: usize,
+    /// ホップ数ごとの呼の数
+    hops: BTreeMap<usize, usize>,
+    /// 総距離ごとの呼の数
+    distance: BTreeMap<isize, usize>,
+    /// 伸長率の階級ごとの呼の数
+    stretch: BTreeMap<isize, usize>,
+    /// 最小ホップ数を超えたホップ数の合計
+    extra_hops: usize,
+    /// 伸長率の合計と、それを求めた呼の数
+    stretch_sum: f64,
+    stretch_calls: usize,
+    max_stretch: f64,
+}
+
+impl RouteStats {
+    /// 全実行の確立できた呼の経路を集計する
+    ///
+    /// 伸長率は最短距離が正のノード対の呼についてだけ求める
+    /// （最短距離が0なら比が定まらない）。
+    pub fn new(topology: &Topology, results: &[RunResult]) -> Self {
+        let node_num = topology.node_num();
+        let shortest = ShortestPath::new(topology);
+        let min_hops = topology.hop_counts();
+        let mut stats = RouteStats::default();
+        for route in results.iter().flat_map(|result| &result.routes) {
+            stats.calls += 1;
+            *stats.hops.entry(route.hops).or_default() += 1;
+            *stats.distance.entry(route.distance).or_default() += 1;
+            stats.extra_hops += route.hops - min_hops[route.source * node_num + route.destination];
+
+            let shortest = shortest
+                .distance(route.source, route.destination)
+                .expect("accepted call has a path");
+            if shortest > 0 {
+                let bin = (route.distance - shortest) * STRETCH_BINS_PER_UNIT / shortest;
+                *stats.stretch.entry(bin).or_default() += 1;
+                let stretch = route.distance as f64 / shortest as f64;
+                stats.stretch_sum += stretch;
+                stats.stretch_calls += 1;
+                stats.max_stretch = stats.max_stretch.max(stretch);
+            }
+        }
+        stats
+    }
+
+    /// 確立できた呼の数
+    pub fn calls(&self) -> usize {
+        self.calls
+    }
+
+    /// 確立できた呼のうち`calls`個が占める割合（呼が無ければ0）
+    pub fn share(&self, calls: usize) -> f64 {
+        ratio(calls as f64, self.calls).unwrap_or(0.0)
+    }
+
+    /// 経路の平均ホップ数（呼が無ければ`None`）
+    pub fn mean_hops(&self) -> Option<f64> {
+        let total: usize = self.hops.iter().map(|(hops, calls)| hops * calls).sum();
+        ratio(total as f64, self.calls)
+    }
+
+    /// 最小ホップ数を超えた平均ホップ数（1呼あたりの余分なリンク数、呼が無ければ`None`）
+    pub fn mean_extra_hops(&self) -> Option<f64> {
+        ratio(self.extra_hops as f64, self.calls)
+    }
+
+    /// 経路の平均総距離（呼が無ければ`None`）
+    pub fn mean_distance(&self) -> Option<f64> {
+        let total: isize = self
+            .distance
+            .iter()
+            .map(|(&distance, &calls)| distance * calls as isize)
+            .sum();
+        ratio(total as f64, self.calls)
+    }
+
+    /// 平均伸長率（伸長率を求めた呼が無ければ`None`）
+    pub fn mean_stretch(&self) -> Option<f64> {
+        ratio(self.stretch_sum, self.stretch_calls)
+    }
+
+    /// 最大の伸長率（伸長率を求めた呼が無ければ`None`）
+    pub fn max_stretch(&self) -> Option<f64> {
+        (self.stretch_calls > 0).then_some(self.max_stretch)
+    }
+
+    /// ホップ数ごとの呼の数（ホップ数の小さい順）
+    pub fn hop_histogram(&self) -> Vec<(usize, usize)> {
+        self.hops
+            .iter()
+            .map(|(&hops, &calls)| (hops, calls))
+            .collect()
+    }
+
+    /// 総距離ごとの呼の数（距離の短い順）
+    pub fn distance_histogram(&self) -> Vec<(isize, usize)> {
+        self.distance
+            .iter()
+            .map(|(&distance, &calls)| (distance, calls))
+            .collect()
+    }
+
+    /// 伸長率の階級の下限ごとの呼の数（伸長率の小さい順）
+    pub fn stretch_histogram(&self) -> Vec<(f64, usize)> {
+        self.stretch
+            .iter()
+            .map(|(&bin, &calls)| (1.0 + bin as f64 / STRETCH_BINS_PER_UNIT as f64, calls))
+            .collect()
+    }
+}
+
+/// `total / count`（`count`が0なら`None`）
+fn ratio(total: f64, count: usize) -> Option<f64> {
+    (count > 0).then(|| total / count as f64)
+}
@@ -0,0 +1,687 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use rand::Rng;
+use rayon::prelude::*;
+
+use crate::process::{ArrivalProcess, HoldingTime};
+use crate::reservation::TrunkReservation;
+use crate::rng;
+use crate::router::{self, Router};
+use crate::stats::{self, StoppingRule};
+use crate::topology::Topology;
+use crate::traffic::{TrafficClasses, TrafficMatrix};
+
+/// 発生する呼
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Call {
+    /// 到着時刻
+    pub time: f64,
+    /// 始点ノード
+    pub source: usize,
+    /// 終点ノード
+    pub destination: usize,
+    /// 保持時間
+    pub holding: f64,
+    /// クラス番号
+    pub class: usize,
+    /// 各リンクで使う帯域
+    pub demand: isize,
+}
+
+/// 1回のシミュレーション実行の結果
+///
+/// 呼の数はウォームアップ期間の呼を除いたもの。リンクの統計は、集計する
+/// 最初の呼の到着から最後の呼の到着までの期間について求める。
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RunResult {
+    /// ウォームアップとして集計から除いた先頭の呼の数
+    pub warmup: usize,
+    /// 発生した呼の数
+    pub total_attempts: usize,
+    /// 確立できた呼の数
+    pub total_success: usize,
+    /// クラスごとの結果（添字はクラス番号）
+    pub classes: Vec<ClassResult>,
+    /// リンクの統計を求めた期間の長さ
+    pub duration: f64,
+    /// リンクごとの結果（添字はリンク番号）
+    pub links: Vec<LinkResult>,
+    /// ノード対（始点, 終点）ごとの結果（呼が発生した対のみ）
+    pub pairs: BTreeMap<(usize, usize), PairResult>,
+    /// 確立できた呼の経路（到着順）
+    pub routes: Vec<RouteResult>,
+}
+
+impl RunResult {
+    /// 呼損率（呼が発生しなかった実行は呼損が無いので0）
+    pub fn call_loss_rate(&self) -> f64 {
+        loss_rate(self.total_attempts, self.total_success)
+    }
+
+    /// 呼損になった呼の数
+    pub fn total_blocked(&self) -> usize {
+        self.total_attempts - self.total_success
+    }
+
+    /// リンク`link`の時間平均の使用帯域（期間の長さが0なら0）
+    pub fn mean_occupancy(&self, link: usize) -> f64 {
+        if self.duration > 0.0 {
+            self.links[link].occupancy / self.duration
+        } else {
+            0.0
+        }
+    }
+
+    /// リンク`link`（容量`capacity`）の時間平均の使用率
+    pub fn utilization(&self, link: usize, capacity: isize) -> f64 {
+        self.mean_occupancy(link) / capacity as f64
+    }
+}
+
+/// 1回の実行での1つのノード対の結果
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PairResult {
+    /// 発生した呼の数
+    pub total_attempts: usize,
+    /// 確立できた呼の数
+    pub total_success: usize,
+}
+
+impl PairResult {
+    /// 呼損率（呼が発生しなければ0）
+    pub fn call_loss_rate(&self) -> f64 {
+        loss_rate(self.total_attempts, self.total_success)
+    }
+}
+
+/// 確立できた1つの呼の経路
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RouteResult {
+    /// 始点ノード
+    pub source: usize,
+    /// 終点ノード
+    pub destination: usize,
+    /// 経路のホップ数（リンク数）
+    pub hops: usize,
+    /// 経路の総距離
+    pub distance: isize,
+}
+
+/// 1回の実行での1つのリンクの結果
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LinkResult {
+    /// 使用中の帯域の時間積分（帯域 × 時間）
+    pub occupancy: f64,
+    /// このリンクがボトルネックになって呼損になった呼の数
+    ///
+    /// 方式が選んだ経路（経路が無ければ距離の最短経路）上で、呼の帯域分の
+    /// 空き容量が無かった（トランク予約の条件を満たさなかった）リンクを
+    /// ボトルネックとする。1つの呼で複数のリンクが数えられることがある。
+    pub blocked: usize,
+}
+
+/// 1回の実行での1つのクラスの結果
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ClassResult {
+    /// 発生した呼の数
+    pub total_attempts: usize,
+    /// 確立できた呼の数
+    pub total_success: usize,
+}
+
+impl ClassResult {
+    /// 呼損率（呼が発生しなかったクラスは0）
+    pub fn call_loss_rate(&self) -> f64 {
+        loss_rate(self.total_attempts, self.total_success)
+    }
+}
+
+/// 発生した呼のうち呼損になった割合
+///
+/// 呼が発生しなければ呼損も無いので0とし、集計や結果ファイルにNaNを持ち込まない。
+fn loss_rate(total_attempts: usize, total_success: usize) -> f64 {
+    if total_attempts == 0 {
+        0.0
+    } else {
+        (total_attempts - total_success) as f64 / total_attempts as f64
+    }
+}
+
+/// ウォームアップ期間（集計から除く先頭の呼）の決め方
+///
+/// 文字列からは `none`、呼の数、`mser5` の形式で作る。
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Warmup {
+    /// 全ての呼を集計する
+    #[default]
+    None,
+    /// 先頭の決まった数の呼を除く
+    Calls(usize),
+    /// 呼損の有無（0/1）の列にMSER-5法を適用して実行ごとに決める
+    Mser5,
+}
+
+impl Warmup {
+    /// 呼ごとの呼損の有無`blocked`から、集計から除く先頭の呼の数を求める
+    pub fn truncation(&self, blocked: &[bool]) -> usize {
+        match *self {
+            Warmup::None => 0,
+            Warmup::Calls(calls) => calls.min(blocked.len()),
+            Warmup::Mser5 => {
+                let values: Vec<f64> = blocked.iter().map(|&b| b as u8 as f64).collect();
+                stats::mser(&values, 5)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Warmup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warmup::None => write!(f, "none"),
+            Warmup::Calls(calls) => write!(f, "{}", calls),
+            Warmup::Mser5 => write!(f, "mser5"),
+        }
+    }
+}
+
+impl FromStr for Warmup {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        match spec {
+            "none" => Ok(Warmup::None),
+            "mser5" => Ok(Warmup::Mser5),
+            _ => spec.parse().map(Warmup::Calls).map_err(|_| {
+                format!(
+                    "invalid warm-up `{}` (expected none, mser5 or a number of calls)",
+                    spec
+                )
+            }),
+        }
+    }
+}
+
+/// 呼の終了イベント
+struct Departure {
+    time: f64,
+    /// 確立した順番（同時刻の終了を決まった順に処理するため）
+    sequence: usize,
+    /// 呼が使っているリンク
+    links: Vec<usize>,
+    /// 呼が各リンクで使っている帯域
+    demand: isize,
+}
+
+impl PartialEq for Departure {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Departure {}
+
+impl Ord for Departure {
+    // BinaryHeapで時刻の早い順に取り出せるように逆順にする
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .time
+            .total_cmp(&self.time)
+            .then_with(|| other.sequence.cmp(&self.sequence))
+    }
+}
+
+impl PartialOrd for Departure {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// 呼損率の離散事象シミュレーション
+///
+/// 呼は [`ArrivalProcess`] に従って到着し、始点・終点は [`TrafficMatrix`] の
+/// 値の比でランダムに選ぶ（既定は全ノード対で一様）。トラヒック行列が呼量を
+/// 与える場合は、平均到着率を全体の呼量 / 平均保持時間`n`にする。呼は [`TrafficClasses`]
+/// から選んだクラスの帯域を経路上の各リンクで使う。
+/// 経路上の全リンクに帯域分の空き容量があれば呼を受け付ける（トランク予約を
+/// 設定した場合は迂回経路の呼に [`TrunkReservation`] の条件も課し、方式には
+/// [`Router::find_reserved_path`] で条件を満たす経路を選ばせる）。
+/// 確立した呼は平均`n`の [`HoldingTime`] に従う保持時間の後に終了し、
+/// リンク容量を解放する。終了と到着が同時刻の場合は終了を先に処理する。
+///
+/// 既定の設定（1タイムユニットごとの等間隔到着・一定の保持時間）では、
+/// 各呼を`n`個後の呼が発生する直前に解放する従来のモデルと一致する。
+pub struct Simulator<'a> {
+    topology: &'a Topology,
+    /// 1回の実行で発生させる呼の数
+    max_attempts: usize,
+    /// 呼の到着過程
+    arrival: ArrivalProcess,
+    /// 保持時間分布
+    holding: HoldingTime,
+    /// ノード対ごとの呼量またはその重み
+    traffic: TrafficMatrix,
+    /// 呼のクラス
+    classes: TrafficClasses,
+    /// ウォームアップ期間の決め方
+    warmup: Warmup,
+    /// 迂回呼に対するトランク予約（`None`なら予約しない）
+    reservation: Option<TrunkReservation>,
+}
+
+impl<'a> Simulator<'a> {
+    pub fn new(topology: &'a Topology, max_attempts: usize) -> Self {
+        Simulator {
+            topology,
+            max_attempts,
+            arrival: ArrivalProcess::default(),
+            holding: HoldingTime::default(),
+            traffic: TrafficMatrix::uniform(topology.node_num()),
+            classes: TrafficClasses::default(),
+            warmup: Warmup::default(),
+            reservation: None,
+        }
+    }
+
+    /// 到着過程を設定する
+    pub fn with_arrival(mut self, arrival: ArrivalProcess) -> Self {
+        self.arrival = arrival;
+        self
+    }
+
+    /// 保持時間分布を設定する
+    pub fn with_holding(mut self, holding: HoldingTime) -> Self {
+        self.holding = holding;
+        self
+    }
+
+    /// トラヒック行列を設定する（ノード数はトポロジと同じであること）
+    pub fn with_traffic(mut self, traffic: TrafficMatrix) -> Self {
+        assert_eq!(
+            traffic.node_num(),
+            self.topology.node_num(),
+            "traffic matrix size must match the topology"
+        );
+        self.traffic = traffic;
+        self
+    }
+
+    /// 呼のクラスを設定する
+    pub fn with_classes(mut self, classes: TrafficClasses) -> Self {
+        self.classes = classes;
+        self
+    }
+
+    /// ウォームアップ期間の決め方を設定する
+    pub fn with_warmup(mut self, warmup: Warmup) -> Self {
+        self.warmup = warmup;
+        self
+    }
+
+    /// 迂回呼に対するトランク予約を設定する
+    pub fn with_reservation(mut self, reservation: TrunkReservation) -> Self {
+        self.reservation = Some(reservation);
+        self
+    }
+
+    /// 平均保持時間を`n`タイムユニットとして1回シミュレーションする
+    pub fn run<R: Rng>(&self, router: &mut dyn Router, n: usize, rng: &mut R) -> RunResult {
+        let calls = self.generate_calls(n, rng);
+        let router_seed = rng.gen();
+        self.run_calls(router, &calls, router_seed)
+    }
+
+    /// 平均保持時間が`n`のときに1回の実行で発生する呼の列を作る
+    ///
+    /// 呼を発生させるノード対が無ければ（ノードが2つ未満など）空の列を返す。
+    pub fn generate_calls<R: Rng>(&self, n: usize, rng: &mut R) -> Vec<Call> {
+        let Some(pairs) = self.traffic.sampler() else {
+            return Vec::new();
+        };
+        let classes = self.classes.sampler();
+        let mut arrivals = self.arrival(n).arrivals();
+        (0..self.max_attempts)
+            .map(|_| {
+                let time = arrivals.next_time(rng);
+                let (source, destination) = pairs.sample(rng);
+                let class = classes.sample(rng);
+                Call {
+                    time,
+                    source,
+                    destination,
+                    holding: self.holding.sample(n as f64, rng),
+                    class,
+                    demand: self.classes.demand(class),
+                }
+            })
+            .collect()
+    }
+
+    /// 平均保持時間が`n`のときの到着過程
+    ///
+    /// トラヒック行列が呼量（アーラン）を与える場合は、各ノード対の到着率が
+    /// 呼量 / `n`になるように全体の平均到着率を合わせる（`n`が0なら設定のまま）。
+    pub fn arrival(&self, n: usize) -> ArrivalProcess {
+        match self.traffic.offered_load() {
+            Some(load) if n > 0 => self.arrival.with_mean_rate(load / n as f64),
+            _ => self.arrival,
+        }
+    }
+
+    /// 与えられた呼の列を到着順に発生させてシミュレーションする
+    ///
+    /// 結果はウォームアップ期間の後の呼だけを集計する。
+    /// 経路制御方式は実行の開始時に`router_seed`で初期化する。
+    pub fn run_calls(
+        &self,
+        router: &mut dyn Router,
+        calls: &[Call],
+        router_seed: u64,
+    ) -> RunResult {
+        // リンクの空き容量（添字はリンク番号）
+        let mut bandwidth = self.topology.capacities();
+        // 確立中の呼の終了イベント
+        let mut departures: BinaryHeap<Departure> = BinaryHeap::new();
+        router.reset(router_seed);
+        // 呼ごとの呼損の有無
+        let mut blocked = Vec::with_capacity(calls.len());
+        // リンクの使用帯域の変化（時刻, リンク, 増分）
+        let mut changes: Vec<(f64, usize, isize)> = Vec::new();
+        // 確立できた呼の経路（呼の番号, ホップ数, 総距離）
+        let mut routes: Vec<(usize, usize, isize)> = Vec::new();
+        // 呼損の原因になったリンク（呼の番号, リンク）
+        let mut bottlenecks: Vec<(usize, usize)> = Vec::new();
+        // 経路が無かった呼のボトルネックを調べるノード対ごとの最短経路
+        let mut shortest: HashMap<(usize, usize), Option<Vec<usize>>> = HashMap::new();
+
+        for call in calls {
+            // 到着時刻までに終了した呼のリンク容量を解放
+            while departures
+                .peek()
+                .is_some_and(|departure| departure.time <= call.time)
+            {
+                let departure = departures.pop().expect("peeked departure");
+                for link in departure.links {
+                    bandwidth[link] += departure.demand;
+                    changes.push((departure.time, link, -departure.demand));
+                }
+            }
+
+            let path = match &self.reservation {
+                Some(reservation) => router.find_reserved_path(
+                    call.source,
+                    call.destination,
+                    &bandwidth,
+                    call.demand,
+                    reservation,
+                ),
+                None => router.find_path(call.source, call.destination, &bandwidth, call.demand),
+            };
+            let links = path.and_then(|path| self.topology.path_links(&path));
+            match links {
+                Some(links) if self.admits(call, &links, &bandwidth) => {
+                    let distance = links
+                        .iter()
+                        .map(|&link| self.topology.links()[link].distance)
+                        .sum();
+                    routes.push((blocked.len(), links.len(), distance));
+                    // リンク容量を減少
+                    for &link in &links {
+                        bandwidth[link] -= call.demand;
+                        changes.push((call.time, link, call.demand));
+                    }
+                    departures.push(Departure {
+                        time: call.time + call.holding,
+                        sequence: blocked.len(),
+                        links,
+                        demand: call.demand,
+                    });
+                    blocked.push(false);
+                }
+                links => {
+                    let links = links.or_else(|| {
+                        shortest
+                            .entry((call.source, call.destination))
+                            .or_insert_with(|| {
+                                router::shortest_path(self.topology, call.source, call.destination)
+                                    .and_then(|path| self.topology.path_links(&path))
+                            })
+                            .clone()
+                    });
+                    for link in self.lacking(call, links.as_deref().unwrap_or_default(), &bandwidth)
+                    {
+                        bottlenecks.push((blocked.len(), link));
+                    }
+                    router.blocked(call.source, call.destination);
+                    blocked.push(true);
+                }
+            }
+        }
+
+        let warmup = self.warmup.truncation(&blocked);
+        let mut result = RunResult {
+            warmup,
+            classes: vec![ClassResult::default(); self.classes.len()],
+            links: vec![LinkResult::default(); bandwidth.len()],
+            ..RunResult::default()
+        };
+
+        // 集計する最初の呼の到着から最後の呼の到着までの使用帯域を積分する
+        if let (Some(first), Some(last)) = (calls.get(warmup), calls.last()) {
+            let (start, end) = (first.time, last.time);
+            result.duration = end - start;
+            let mut used = vec![0; bandwidth.len()];
+            let mut since = vec![start; bandwidth.len()];
+            for &(time, link, delta) in &changes {
+                let time = time.clamp(start, end);
+                result.links[link].occupancy += used[link] as f64 * (time - since[link]);
+                since[link] = time;
+                used[link] += delta;
+            }
+            for (link, result) in result.links.iter_mut().enumerate() {
+                result.occupancy += used[link] as f64 * (end - since[link]);
+            }
+        }
+        for &(call, link) in &bottlenecks {
+            if call >= warmup {
+                result.links[link].blocked += 1;
+            }
+        }
+
+        for &(call, hops, distance) in &routes {
+            if call >= warmup {
+                result.routes.push(RouteResult {
+                    source: calls[call].source,
+                    destination: calls[call].destination,
+                    hops,
+                    distance,
+                });
+            }
+        }
+
+        for (call, &blocked) in calls.iter().zip(&blocked).skip(warmup) {
+            let class = &mut result.classes[call.class];
+            let pair = result
+                .pairs
+                .entry((call.source, call.destination))
+                .or_default();
+            result.total_attempts += 1;
+            class.total_attempts += 1;
+            pair.total_attempts += 1;
+            if !blocked {
+                result.total_success += 1;
+                class.total_success += 1;
+                pair.total_success += 1;
+            }
+        }
+        result
+    }
+
+    /// 同じ条件で`runs`回繰り返し実行する
+    ///
+    /// `r`回目の実行には[`rng::stream`]`(seed, n, r)`の乱数列を使う。
+    /// 実行はスレッドプールで並列に行い、方式は実行ごとに複製して
+    /// 初期化するので、結果はスレッド数によらず順番も値も同じになる。
+    pub fn replicate(
+        &self,
+        router: &dyn Router,
+        n: usize,
+        runs: usize,
+        seed: u64,
+    ) -> Vec<RunResult> {
+        self.replicate_range(router, n, 0..runs, seed)
+    }
+
+    /// 呼損率の信頼区間が`rule`を満たすまで実行を繰り返す
+    ///
+    /// 乱数列は [`Simulator::replicate`] と同じなので、先頭の実行の結果は
+    /// 回数を固定した場合と一致する。実行はスレッド数ずつまとめて並列に行い、
+    /// 規則を満たした後の余分な実行は捨てる。
+    pub fn replicate_until(
+        &self,
+        router: &dyn Router,
+        n: usize,
+        seed: u64,
+        rule: &StoppingRule,
+    ) -> Vec<RunResult> {
+        let batch = rayon::current_num_threads();
+        let mut results = Vec::new();
+        let mut losses = Vec::new();
+        while !rule.is_satisfied(&losses) {
+            let start = results.len();
+            for result in self.replicate_range(router, n, start..start + batch, seed) {
+                if rule.is_satisfied(&losses) {
+                    break;
+                }
+                losses.push(result.call_loss_rate());
+                results.push(result);
+            }
+        }
+        results
+    }
+
+    /// `runs`の範囲の実行を並列に行う
+    fn replicate_range(
+        &self,
+        router: &dyn Router,
+        n: usize,
+        runs: Range<usize>,
+        seed: u64,
+    ) -> Vec<RunResult> {
+        runs.into_par_iter()
+            .map_init(
+                || router.clone_box(),
+                |router, run| {
+                    let mut rng = rng::stream(seed, n as u64, run as u64);
+                    self.run(router.as_mut(), n, &mut rng)
+                },
+            )
+            .collect()
+    }
+
+    /// 複数の方式に同じ呼の列を流して比較する（共通乱数法）
+    ///
+    /// `r`回目の実行では[`rng::stream`]`(seed, n, r)`から呼の列を1つ作り、
+    /// それを全方式に与える（方式の初期化に使うシードも共通）。戻り値は`[実行][方式]`の順。
+    /// 実行は [`Simulator::replicate`] と同じく並列に行う。
+    pub fn compare(
+        &self,
+        routers: &[Box<dyn Router>],
+        n: usize,
+        runs: usize,
+        seed: u64,
+    ) -> Vec<Vec<RunResult>> {
+        self.compare_range(routers, n, 0..runs, seed)
+    }
+
+    /// 全方式の呼損率の信頼区間が`rule`を満たすまで比較の実行を繰り返す
+    ///
+    /// 乱数列は [`Simulator::compare`] と同じで、止め方は
+    /// [`Simulator::replicate_until`] と同じ。
+    pub fn compare_until(
+        &self,
+        routers: &[Box<dyn Router>],
+        n: usize,
+        seed: u64,
+        rule: &StoppingRule,
+    ) -> Vec<Vec<RunResult>> {
+        let batch = rayon::current_num_threads();
+        let mut results: Vec<Vec<RunResult>> = Vec::new();
+        // 方式ごとの実行ごとの呼損率
+        let mut losses: Vec<Vec<f64>> = vec![Vec::new(); routers.len()];
+        let satisfied = |losses: &[Vec<f64>]| losses.iter().all(|loss| rule.is_satisfied(loss));
+        while !satisfied(&losses) {
+            let start = results.len();
+            for run in self.compare_range(routers, n, start..start + batch, seed) {
+                if satisfied(&losses) {
+                    break;
+                }
+                for (loss, result) in losses.iter_mut().zip(&run) {
+                    loss.push(result.call_loss_rate());
+                }
+                results.push(run);
+            }
+        }
+        results
+    }
+
+    /// `runs`の範囲の比較の実行を並列に行う
+    fn compare_range(
+        &self,
+        routers: &[Box<dyn Router>],
+        n: usize,
+        runs: Range<usize>,
+        seed: u64,
+    ) -> Vec<Vec<RunResult>> {
+        runs.into_par_iter()
+            .map_init(
+                || {
+                    routers
+                        .iter()
+                        .map(|router| router.clone_box())
+                        .collect::<Vec<_>>()
+                },
+                |routers, run| {
+                    let mut rng = rng::stream(seed, n as u64, run as u64);
+                    let calls = self.generate_calls(n, &mut rng);
+                    let router_seed = rng.gen();
+                    routers
+                        .iter_mut()
+                        .map(|router| self.run_calls(router.as_mut(), &calls, router_seed))
+                        .collect()
+                },
+            )
+            .collect()
+    }
+
+    /// 経路上のリンクのうち、呼を受け付ける条件を満たさないもの
+    fn lacking(&self, call: &Call, links: &[usize], bandwidth: &[isize]) -> Vec<usize> {
+        let threshold = |link: usize| match &self.reservation {
+            Some(reservation)
+                if links.len() > reservation.min_hops(call.source, call.destination) =>
+            {
+                reservation.threshold(link)
+            }
+            _ => 0,
+        };
+        links
+            .iter()
+            .copied()
+            .filter(|&link| bandwidth[link] - call.demand < threshold(link))
+            .collect()
+    }
+
+    // 通信経路上のリンク容量のチェック
+    fn admits(&self, call: &Call, links: &[usize], bandwidth: &[isize]) -> bool {
+        match &self.reservation {
+            Some(reservation) => {
+                reservation.admits(call.source, call.destination, links, bandwidth, call.demand)
+            }
+            None => links.iter().all(|&link| bandwidth[link] >= call.demand),
+        }
+    }
+}
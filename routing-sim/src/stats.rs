@@ -0,0 +1,278 @@
+//! 実行結果の集計
+
+/// 標本平均
+pub fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// 不偏標本分散（値が1つ以下なら0）
+pub fn sample_variance(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean = mean(values);
+    values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64
+}
+
+/// Jainの公平性指標 `(Σx)² / (n Σx²)`
+///
+/// 値が全て0なら1（全て等しい）とする。値が無ければNaN。
+pub fn jain_index(values: &[f64]) -> f64 {
+    let sum: f64 = values.iter().sum();
+    let sum_of_squares: f64 = values.iter().map(|v| v * v).sum();
+    if values.is_empty() {
+        f64::NAN
+    } else if sum_of_squares == 0.0 {
+        1.0
+    } else {
+        sum * sum / (values.len() as f64 * sum_of_squares)
+    }
+}
+
+/// 同じ呼の列で評価した2方式の呼損率の差（対応のある差）
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PairedDifference {
+    /// 差の平均
+    pub mean: f64,
+    /// 差の不偏標本分散
+    pub variance: f64,
+    /// 差の平均の標準誤差
+    pub std_error: f64,
+}
+
+impl PairedDifference {
+    /// 実行ごとの値の組`(a, b)`から`a - b`の統計量を求める
+    pub fn new(pairs: impl IntoIterator<Item = (f64, f64)>) -> Self {
+        let differences: Vec<f64> = pairs.into_iter().map(|(a, b)| a - b).collect();
+        let variance = sample_variance(&differences);
+        PairedDifference {
+            mean: mean(&differences),
+            variance,
+            std_error: (variance / differences.len() as f64).sqrt(),
+        }
+    }
+}
+
+/// 実行ごとの値から求めた平均の信頼区間（Student-t分布による）
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConfidenceInterval {
+    /// 標本平均
+    pub mean: f64,
+    /// 標本標準偏差
+    pub std_dev: f64,
+    /// 信頼区間の半幅（値が1つ以下なら無限大）
+    pub half_width: f64,
+    /// 信頼水準（0.95など）
+    pub level: f64,
+}
+
+impl ConfidenceInterval {
+    /// 値`values`の平均の信頼水準`level`の信頼区間
+    pub fn new(values: &[f64], level: f64) -> Self {
+        let count = values.len();
+        let std_dev = sample_variance(values).sqrt();
+        let half_width = if count < 2 {
+            f64::INFINITY
+        } else {
+            let t = student_t_quantile(0.5 + level / 2.0, (count - 1) as f64);
+            t * std_dev / (count as f64).sqrt()
+        };
+        ConfidenceInterval {
+            mean: mean(values),
+            std_dev,
+            half_width,
+            level,
+        }
+    }
+
+    /// 下限
+    pub fn lower(&self) -> f64 {
+        self.mean - self.half_width
+    }
+
+    /// 上限
+    pub fn upper(&self) -> f64 {
+        self.mean + self.half_width
+    }
+
+    /// 平均に対する半幅の比（平均が0なら、半幅も0のときだけ0、それ以外は無限大）
+    pub fn relative_half_width(&self) -> f64 {
+        if self.mean == 0.0 {
+            if self.half_width == 0.0 {
+                0.0
+            } else {
+                f64::INFINITY
+            }
+        } else {
+            self.half_width / self.mean.abs()
+        }
+    }
+}
+
+/// 自由度`df`のStudent-t分布の`p`分位点（0 < p < 1）
+pub fn student_t_quantile(p: f64, df: f64) -> f64 {
+    if p == 0.5 {
+        return 0.0;
+    }
+    if p < 0.5 {
+        return -student_t_quantile(1.0 - p, df);
+    }
+    // 上側確率がpを下回る点を二分法で求める
+    let mut low = 0.0;
+    let mut high = 1.0;
+    while student_t_cdf(high, df) < p {
+        high *= 2.0;
+    }
+    for _ in 0..200 {
+        let middle = (low + high) / 2.0;
+        if student_t_cdf(middle, df) < p {
+            low = middle;
+        } else {
+            high = middle;
+        }
+        if high - low <= 1e-12 * high {
+            break;
+        }
+    }
+    (low + high) / 2.0
+}
+
+/// 自由度`df`のStudent-t分布の累積分布関数
+pub fn student_t_cdf(t: f64, df: f64) -> f64 {
+    // P(T <= t) = 1 - I_x(df/2, 1/2) / 2  (t >= 0, x = df / (df + t^2))
+    let tail = regularized_incomplete_beta(df / (df + t * t), df / 2.0, 0.5) / 2.0;
+    if t >= 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// 正則化不完全ベータ関数 I_x(a, b)
+fn regularized_incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // 連分数の収束の速い側で計算する
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - front * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+/// 不完全ベータ関数の連分数（Lentz法）
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut result = d;
+    for m in 1..=300 {
+        let m = m as f64;
+        for numerator in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ] {
+            d = 1.0 + numerator * d;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = 1.0 + numerator / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            result *= d * c;
+        }
+        if (d * c - 1.0).abs() < 1e-15 {
+            break;
+        }
+    }
+    result
+}
+
+/// ガンマ関数の対数（Lanczos近似、x > 0）
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let mut series = 1.000000000190015;
+    for (i, coefficient) in COEFFICIENTS.iter().enumerate() {
+        series += coefficient / (x + 1.0 + i as f64);
+    }
+    let t = x + 5.5;
+    (x + 0.5) * t.ln() - t + (2.5066282746310005 * series / x).ln()
+}
+
+/// 信頼区間の相対半幅が目標以下になるまで実行を追加する停止規則
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StoppingRule {
+    /// 信頼水準
+    pub level: f64,
+    /// 目標とする相対半幅（半幅 / 平均）
+    pub relative_half_width: f64,
+    /// 最少の実行回数
+    pub min_runs: usize,
+    /// 最多の実行回数（目標に届かなくてもここで止める）
+    pub max_runs: usize,
+}
+
+impl StoppingRule {
+    /// これまでの値`values`で実行を止めてよいか
+    pub fn is_satisfied(&self, values: &[f64]) -> bool {
+        let count = values.len();
+        if count >= self.max_runs {
+            return true;
+        }
+        count >= self.min_runs.max(2)
+            && ConfidenceInterval::new(values, self.level).relative_half_width()
+                <= self.relative_half_width
+    }
+}
+
+/// MSER-m法で初期過渡期として捨てる観測値の数を求める
+///
+/// 観測値を`batch`個ずつのバッチ平均`Y_1..Y_k`にまとめ、
+/// `MSER(d) = Σ_{j>d} (Y_j - Ȳ_d)^2 / (k - d)^2`を最小にする`d`
+/// （`d <= k / 2`）を選んで`d * batch`を返す。端数のバッチは使わない。
+pub fn mser(values: &[f64], batch: usize) -> usize {
+    let batches: Vec<f64> = values.chunks_exact(batch).map(mean).collect();
+    let k = batches.len();
+    if k < 2 {
+        return 0;
+    }
+
+    // 後ろからの和と二乗和で各dの分散を求める
+    let mut sum = 0.0;
+    let mut square_sum = 0.0;
+    let mut best = (f64::INFINITY, 0);
+    for d in (0..k).rev() {
+        sum += batches[d];
+        square_sum += batches[d] * batches[d];
+        if d > k / 2 {
+            continue;
+        }
+        let count = (k - d) as f64;
+        let squared_error = (square_sum - sum * sum / count).max(0.0);
+        let statistic = squared_error / (count * count);
+        // 同じ値なら捨てる数の少ない方を選ぶ
+        if statistic <= best.0 {
+            best = (statistic, d);
+        }
+    }
+    best.1 * batch
+}
@@ -0,0 +1,500 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+pub const MAX: isize = isize::MAX;
+
+/// ノード間のリンク（無向）
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Link {
+    /// 接続元ノード
+    pub node1: usize,
+    /// 接続先ノード
+    pub node2: usize,
+    /// ノード間の距離
+    pub distance: isize,
+    /// リンク容量
+    pub capacity: isize,
+}
+
+/// 隣接リストの要素
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Adjacent {
+    /// 隣接ノード
+    pub node: usize,
+    /// そのノードへのリンクの番号
+    pub link: usize,
+}
+
+/// `distance.txt` から読み込んだネットワーク
+///
+/// ノード数はファイル中のノード番号の個数から決まる。隣接関係はCSR形式で
+/// 保持するので、ノード数が数千になっても記憶量はリンク数に比例する。
+#[derive(Clone, Debug)]
+pub struct Topology {
+    node_num: usize,
+    links: Vec<Link>,
+    /// ノード`i`の隣接リストは`adjacency[offsets[i]..offsets[i + 1]]`
+    offsets: Vec<usize>,
+    adjacency: Vec<Adjacent>,
+}
+
+impl Topology {
+    /// ファイルからトポロジを読み込む
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, TopologyError> {
+        let file = File::open(path)?;
+        Self::from_reader(BufReader::new(file))
+    }
+
+    /// トポロジファイルを解析する
+    ///
+    /// 各行は `接続元ノード 接続先ノード 距離 リンク容量` の4つの値からなる。
+    /// `#`から行末まではコメントとして読み飛ばし、空行も無視する。
+    /// ノード数はリンクに現れたノード番号の個数で、番号は0から隙間なく
+    /// 使わなければならない（最大の番号がノード数以上ならエラー）。リンクの行より前に
+    /// `nodes <ノード数>` と書くとノード数を明示でき、リンクの無いノードも持てる
+    /// （範囲外のノード番号はエラー）。
+    /// 呼の始点と終点を選べるように、ノードは2つ以上必要。
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, TopologyError> {
+        let mut links: Vec<Link> = Vec::new();
+        // ノード対ごとに最初に現れた行番号
+        let mut link_lines: HashMap<(usize, usize), usize> = HashMap::new();
+        let mut declared_node_num: Option<usize> = None;
+        // `nodes`の値の位置（行, 桁）
+        let mut declared_at: Option<(usize, usize)> = None;
+        // `nodes`が無いときにリンクに現れたノード
+        let mut seen: HashSet<usize> = HashSet::new();
+        // 最大のノード番号と、それが最初に現れた位置（行, 桁）
+        let mut max_node: Option<(usize, usize, usize)> = None;
+        let mut line_count = 0;
+
+        for (index, line) in reader.lines().enumerate() {
+            let content = line?;
+            let line_number = index + 1;
+            line_count = line_number;
+            let error = |column: usize, kind: ParseErrorKind| TopologyError::Parse {
+                line: line_number,
+                column,
+                kind,
+            };
+
+            let fields = split_fields(strip_comment(&content));
+            if fields.is_empty() {
+                continue;
+            }
+
+            if fields[0].1 == "nodes" {
+                if declared_node_num.is_some() {
+                    return Err(error(fields[0].0, ParseErrorKind::DuplicateNodeCount));
+                }
+                if !links.is_empty() {
+                    return Err(error(fields[0].0, ParseErrorKind::NodeCountAfterLinks));
+                }
+                if fields.len() != 2 {
+                    return Err(error(
+                        fields[0].0,
+                        ParseErrorKind::FieldCount {
+                            expected: 2,
+                            found: fields.len(),
+                        },
+                    ));
+                }
+                let (column, value) = fields[1];
+                declared_node_num = Some(parse_field(value).map_err(|kind| error(column, kind))?);
+                declared_at = Some((line_number, column));
+                continue;
+            }
+
+            if fields.len() != 4 {
+                return Err(error(
+                    fields[0].0,
+                    ParseErrorKind::FieldCount {
+                        expected: 4,
+                        found: fields.len(),
+                    },
+                ));
+            }
+            let field = |i: usize| {
+                let (column, value) = fields[i];
+                parse_field::<isize>(value)
+                    .map(|number| (column, number))
+                    .map_err(|kind| error(column, kind))
+            };
+            let node = |i: usize| {
+                let (column, value) = fields[i];
+                let node: usize = parse_field(value).map_err(|kind| error(column, kind))?;
+                match declared_node_num {
+                    Some(node_num) if node >= node_num => Err(error(
+                        column,
+                        ParseErrorKind::NodeOutOfRange { node, node_num },
+                    )),
+                    _ => Ok(node),
+                }
+            };
+
+            let node1 = node(0)?;
+            let node2 = node(1)?;
+            let (distance_column, distance) = field(2)?;
+            let (capacity_column, capacity) = field(3)?;
+
+            if node1 == node2 {
+                return Err(error(fields[1].0, ParseErrorKind::SelfLoop { node: node1 }));
+            }
+            if distance < 0 {
+                return Err(error(
+                    distance_column,
+                    ParseErrorKind::NegativeDistance { distance },
+                ));
+            }
+            if capacity <= 0 {
+                return Err(error(
+                    capacity_column,
+                    ParseErrorKind::NonPositiveCapacity { capacity },
+                ));
+            }
+            let key = (node1.min(node2), node1.max(node2));
+            if let Some(&first_line) = link_lines.get(&key) {
+                return Err(error(
+                    fields[0].0,
+                    ParseErrorKind::DuplicateLink {
+                        node1,
+                        node2,
+                        first_line,
+                    },
+                ));
+            }
+            link_lines.insert(key, line_number);
+            if declared_node_num.is_none() {
+                for (node, column) in [(node1, fields[0].0), (node2, fields[1].0)] {
+                    seen.insert(node);
+                    if max_node.is_none_or(|(max, _, _)| node > max) {
+                        max_node = Some((node, line_number, column));
+                    }
+                }
+            }
+
+            links.push(Link {
+                node1,
+                node2,
+                distance,
+                capacity,
+            });
+        }
+
+        let node_num = match declared_node_num {
+            Some(node_num) => node_num,
+            None => {
+                // 番号に隙間があれば、最大の番号を範囲外とする（誤記で巨大な表を
+                // 確保しないように、番号からノード数を決めない）
+                let node_num = seen.len();
+                if let Some((node, line, column)) = max_node {
+                    if node >= node_num {
+                        return Err(TopologyError::Parse {
+                            line,
+                            column,
+                            kind: ParseErrorKind::NodeOutOfRange { node, node_num },
+                        });
+                    }
+                }
+                node_num
+            }
+        };
+        if node_num < 2 {
+            // 宣言したノード数の値、無ければファイルの末尾を指す
+            let (line, column) = declared_at.unwrap_or((line_count + 1, 1));
+            return Err(TopologyError::Parse {
+                line,
+                column,
+                kind: ParseErrorKind::TooFewNodes { node_num },
+            });
+        }
+        Ok(Self::new(node_num, links))
+    }
+
+    /// ノード数とリンクの一覧からトポロジを作る
+    pub fn new(node_num: usize, links: Vec<Link>) -> Self {
+        let mut degree = vec![0; node_num];
+        for link in &links {
+            degree[link.node1] += 1;
+            degree[link.node2] += 1;
+        }
+
+        let mut offsets = vec![0; node_num + 1];
+        for i in 0..node_num {
+            offsets[i + 1] = offsets[i] + degree[i];
+        }
+
+        let mut fill = offsets.clone();
+        let mut adjacency = vec![Adjacent { node: 0, link: 0 }; offsets[node_num]];
+        for (id, link) in links.iter().enumerate() {
+            adjacency[fill[link.node1]] = Adjacent {
+                node: link.node2,
+                link: id,
+            };
+            fill[link.node1] += 1;
+            adjacency[fill[link.node2]] = Adjacent {
+                node: link.node1,
+                link: id,
+            };
+            fill[link.node2] += 1;
+        }
+
+        Topology {
+            node_num,
+            links,
+            offsets,
+            adjacency,
+        }
+    }
+
+    /// ノード数
+    pub fn node_num(&self) -> usize {
+        self.node_num
+    }
+
+    /// リンクの一覧（添字がリンク番号）
+    pub fn links(&self) -> &[Link] {
+        &self.links
+    }
+
+    /// ノード`node`の隣接リスト
+    pub fn neighbors(&self, node: usize) -> &[Adjacent] {
+        &self.adjacency[self.offsets[node]..self.offsets[node + 1]]
+    }
+
+    /// ノード`u`と`v`を結ぶリンクの番号
+    pub fn link_between(&self, u: usize, v: usize) -> Option<usize> {
+        self.neighbors(u)
+            .iter()
+            .find(|adjacent| adjacent.node == v)
+            .map(|adjacent| adjacent.link)
+    }
+
+    /// 各リンクの容量（添字がリンク番号）
+    pub fn capacities(&self) -> Vec<isize> {
+        self.links.iter().map(|link| link.capacity).collect()
+    }
+
+    /// 経路（ノード列）が通るリンクの番号
+    ///
+    /// 隣り合うノードの間にリンクが無ければ`None`
+    pub fn path_links(&self, path: &[usize]) -> Option<Vec<usize>> {
+        path.windows(2)
+            .map(|w| self.link_between(w[0], w[1]))
+            .collect()
+    }
+
+    /// 経路の総距離
+    pub fn path_distance(&self, path: &[usize]) -> isize {
+        path.windows(2)
+            .filter_map(|w| self.link_between(w[0], w[1]))
+            .map(|link| self.links[link].distance)
+            .sum()
+    }
+
+    /// 全ノード対の最小ホップ数を幅優先探索で求める
+    ///
+    /// `source * node_num + destination`の順に並べ、到達できなければ`usize::MAX`。
+    pub fn hop_counts(&self) -> Vec<usize> {
+        let node_num = self.node_num;
+        let mut hops = vec![usize::MAX; node_num * node_num];
+        let mut queue = VecDeque::new();
+        for source in 0..node_num {
+            let row = &mut hops[source * node_num..(source + 1) * node_num];
+            row[source] = 0;
+            queue.push_back(source);
+            while let Some(node) = queue.pop_front() {
+                for adjacent in self.neighbors(node) {
+                    if row[adjacent.node] == usize::MAX {
+                        row[adjacent.node] = row[node] + 1;
+                        queue.push_back(adjacent.node);
+                    }
+                }
+            }
+        }
+        hops
+    }
+
+    /// ノード数とリンク（番号順）から求めた64ビットのハッシュ値（FNV-1a）
+    ///
+    /// 結果ファイルがどのトポロジで得たものかを照合するために使う。
+    /// プラットフォームや実行によらず同じ値になる。
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut feed = |value: u64| {
+            for byte in value.to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+        feed(self.node_num as u64);
+        for link in &self.links {
+            feed(link.node1 as u64);
+            feed(link.node2 as u64);
+            feed(link.distance as u64);
+            feed(link.capacity as u64);
+        }
+        hash
+    }
+}
+
+/// `#`から行末までのコメントを取り除く
+pub(crate) fn strip_comment(content: &str) -> &str {
+    match content.find('#') {
+        Some(comment) => &content[..comment],
+        None => content,
+    }
+}
+
+/// 行を空白で区切り、各値とその桁位置（1始まり）を返す
+pub(crate) fn split_fields(content: &str) -> Vec<(usize, &str)> {
+    let mut fields = Vec::new();
+    let mut start = None;
+    for (column, (i, c)) in content.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some((column + 1, i)),
+            (true, Some((field_column, field_start))) => {
+                fields.push((field_column, &content[field_start..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((field_column, field_start)) = start {
+        fields.push((field_column, &content[field_start..]));
+    }
+    fields
+}
+
+pub(crate) fn parse_field<T: std::str::FromStr>(value: &str) -> Result<T, ParseErrorKind> {
+    value.parse().map_err(|_| ParseErrorKind::InvalidNumber {
+        value: value.to_string(),
+    })
+}
+
+/// トポロジファイルの読み込みエラー
+#[derive(Debug)]
+pub enum TopologyError {
+    /// ファイルが読めない
+    Io(io::Error),
+    /// 内容が不正（行・桁は1始まり）
+    Parse {
+        line: usize,
+        column: usize,
+        kind: ParseErrorKind,
+    },
+}
+
+/// 入力ファイルの内容の誤り
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// 値の個数が違う
+    FieldCount { expected: usize, found: usize },
+    /// 数値として解釈できない
+    InvalidNumber { value: String },
+    /// ノード数（`nodes`で宣言したもの、無ければリンクに現れたノードの個数）の範囲外
+    NodeOutOfRange { node: usize, node_num: usize },
+    /// 始点と終点が同じリンク
+    SelfLoop { node: usize },
+    /// 同じノード対のリンクが既に定義されている
+    DuplicateLink {
+        node1: usize,
+        node2: usize,
+        first_line: usize,
+    },
+    /// 距離が負
+    NegativeDistance { distance: isize },
+    /// リンク容量が0以下
+    NonPositiveCapacity { capacity: isize },
+    /// `nodes`が2回以上現れた
+    DuplicateNodeCount,
+    /// `nodes`がリンクの行より後にある
+    NodeCountAfterLinks,
+    /// ノードが2つ未満
+    TooFewNodes { node_num: usize },
+    /// トラヒック行列の呼量が負または有限でない
+    InvalidLoad { value: String },
+    /// 同じノード対の呼量が既に定義されている
+    DuplicatePair {
+        source: usize,
+        destination: usize,
+        first_line: usize,
+    },
+}
+
+impl fmt::Display for TopologyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TopologyError::Io(e) => write!(f, "{}", e),
+            TopologyError::Parse { line, column, kind } => {
+                write!(f, "line {}, column {}: {}", line, column, kind)
+            }
+        }
+    }
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::FieldCount { expected, found } => {
+                write!(f, "expected {} fields, found {}", expected, found)
+            }
+            ParseErrorKind::InvalidNumber { value } => write!(f, "invalid number `{}`", value),
+            ParseErrorKind::NodeOutOfRange { node, node_num } => {
+                write!(f, "node {} is out of range for {} nodes", node, node_num)
+            }
+            ParseErrorKind::SelfLoop { node } => write!(f, "self-loop on node {}", node),
+            ParseErrorKind::DuplicateLink {
+                node1,
+                node2,
+                first_line,
+            } => write!(
+                f,
+                "link {}-{} is already defined on line {}",
+                node1, node2, first_line
+            ),
+            ParseErrorKind::NegativeDistance { distance } => {
+                write!(f, "negative distance {}", distance)
+            }
+            ParseErrorKind::NonPositiveCapacity { capacity } => {
+                write!(f, "link capacity must be positive, found {}", capacity)
+            }
+            ParseErrorKind::DuplicateNodeCount => write!(f, "node count is declared twice"),
+            ParseErrorKind::NodeCountAfterLinks => {
+                write!(f, "node count must be declared before any link")
+            }
+            ParseErrorKind::TooFewNodes { node_num } => {
+                write!(f, "at least 2 nodes are needed, found {}", node_num)
+            }
+            ParseErrorKind::InvalidLoad { value } => {
+                write!(f, "load must be a non-negative number, found `{}`", value)
+            }
+            ParseErrorKind::DuplicatePair {
+                source,
+                destination,
+                first_line,
+            } => write!(
+                f,
+                "load for {} -> {} is already defined on line {}",
+                source, destination, first_line
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TopologyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TopologyError::Io(e) => Some(e),
+            TopologyError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for TopologyError {
+    fn from(e: io::Error) -> Self {
+        TopologyError::Io(e)
+    }
+}
@@ -0,0 +1,496 @@
+//! ノード対ごとの呼量（トラヒック行列）と呼のクラス
+//!
+//! ファイルから読み込んだ行列の値は各ノード対の呼量（アーラン）で、ノード対`(s, d)`の
+//! 呼は到着率`weight(s, d) / 平均保持時間`で到着する。組み込みの生成方法
+//! （[`TrafficModel`]）の値は相対的な重みで、全体の呼量は到着過程の到着率と
+//! 平均保持時間で決まる。このときノード対`(s, d)`の呼の到着率は、到着過程の
+//! 到着率に`weight(s, d) / total_weight()`を掛けたものになる。
+//! どちらの場合も呼の始点・終点は値の比で選ぶ。呼の帯域は、
+//! [`TrafficClasses`]の割合で選んだクラスによって決まる。
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::str::FromStr;
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+use crate::topology::{parse_field, split_fields, strip_comment, ParseErrorKind, Topology};
+
+/// ノード対ごとの呼量またはその重み
+#[derive(Clone, Debug)]
+pub struct TrafficMatrix {
+    node_num: usize,
+    /// 値（`source * node_num + destination`）
+    weights: Vec<f64>,
+    /// 全ノード対が同じ値か
+    uniform: bool,
+    /// 値が呼量（アーラン）か（`false`なら相対的な重み）
+    offered: bool,
+}
+
+impl TrafficMatrix {
+    /// 全ノード対の重みが等しい行列
+    pub fn uniform(node_num: usize) -> Self {
+        let mut weights = vec![1.0; node_num * node_num];
+        for i in 0..node_num {
+            weights[i * node_num + i] = 0.0;
+        }
+        TrafficMatrix {
+            node_num,
+            weights,
+            uniform: true,
+            offered: false,
+        }
+    }
+
+    /// 重力モデル：ノード`i`から`j`への重みを両ノードの質量の積とする
+    ///
+    /// ノードの質量はそのノードに接続するリンク容量の合計。
+    pub fn gravity(topology: &Topology) -> Self {
+        let node_num = topology.node_num();
+        let mut mass = vec![0.0; node_num];
+        for link in topology.links() {
+            mass[link.node1] += link.capacity as f64;
+            mass[link.node2] += link.capacity as f64;
+        }
+        Self::from_fn(node_num, |i, j| mass[i] * mass[j])
+    }
+
+    /// ホットスポット：`hot`を始点または終点とするノード対の重みを
+    /// 他のノード対の`factor`倍にする
+    pub fn hot_spot(node_num: usize, hot: usize, factor: f64) -> Self {
+        Self::from_fn(
+            node_num,
+            |i, j| {
+                if i == hot || j == hot {
+                    factor
+                } else {
+                    1.0
+                }
+            },
+        )
+    }
+
+    /// 異なるノード対`(i, j)`の重みを`weight(i, j)`とする行列
+    pub fn from_fn(node_num: usize, mut weight: impl FnMut(usize, usize) -> f64) -> Self {
+        let mut weights = vec![0.0; node_num * node_num];
+        for i in 0..node_num {
+            for j in 0..node_num {
+                if i != j {
+                    weights[i * node_num + j] = weight(i, j);
+                }
+            }
+        }
+        TrafficMatrix {
+            node_num,
+            weights,
+            uniform: false,
+            offered: false,
+        }
+    }
+
+    /// ファイルからトラヒック行列を読み込む
+    pub fn load<P: AsRef<Path>>(path: P, node_num: usize) -> Result<Self, TrafficError> {
+        let file = File::open(path)?;
+        Self::from_reader(BufReader::new(file), node_num)
+    }
+
+    /// `始点ノード 終点ノード 呼量` の行からトラヒック行列を作る
+    ///
+    /// `#`から行末まではコメント。呼量の単位はアーランで、書かれていない
+    /// ノード対の呼量は0。
+    pub fn from_reader<R: BufRead>(reader: R, node_num: usize) -> Result<Self, TrafficError> {
+        let mut weights = vec![0.0; node_num * node_num];
+        // ノード対ごとに最初に現れた行番号
+        let mut pair_lines: HashMap<(usize, usize), usize> = HashMap::new();
+
+        for (index, line) in reader.lines().enumerate() {
+            let content = line?;
+            let line_number = index + 1;
+            let error = |column: usize, kind: ParseErrorKind| TrafficError::Parse {
+                line: line_number,
+                column,
+                kind,
+            };
+
+            let fields = split_fields(strip_comment(&content));
+            if fields.is_empty() {
+                continue;
+            }
+            if fields.len() != 3 {
+                return Err(error(
+                    fields[0].0,
+                    ParseErrorKind::FieldCount {
+                        expected: 3,
+                        found: fields.len(),
+                    },
+                ));
+            }
+            let node = |i: usize| {
+                let (column, value) = fields[i];
+                let node: usize = parse_field(value).map_err(|kind| error(column, kind))?;
+                if node >= node_num {
+                    return Err(error(
+                        column,
+                        ParseErrorKind::NodeOutOfRange { node, node_num },
+                    ));
+                }
+                Ok(node)
+            };
+
+            let source = node(0)?;
+            let destination = node(1)?;
+            let (load_column, value) = fields[2];
+            let load: f64 = parse_field(value).map_err(|kind| error(load_column, kind))?;
+
+            if source == destination {
+                return Err(error(
+                    fields[1].0,
+                    ParseErrorKind::SelfLoop { node: source },
+                ));
+            }
+            if !(load >= 0.0 && load.is_finite()) {
+                return Err(error(
+                    load_column,
+                    ParseErrorKind::InvalidLoad {
+                        value: value.to_string(),
+                    },
+                ));
+            }
+            if let Some(&first_line) = pair_lines.get(&(source, destination)) {
+                return Err(error(
+                    fields[0].0,
+                    ParseErrorKind::DuplicatePair {
+                        source,
+                        destination,
+                        first_line,
+                    },
+                ));
+            }
+            pair_lines.insert((source, destination), line_number);
+            weights[source * node_num + destination] = load;
+        }
+
+        let matrix = TrafficMatrix {
+            node_num,
+            weights,
+            uniform: false,
+            offered: true,
+        };
+        if matrix.total_weight() <= 0.0 {
+            return Err(TrafficError::NoTraffic);
+        }
+        Ok(matrix)
+    }
+
+    /// ノード数
+    pub fn node_num(&self) -> usize {
+        self.node_num
+    }
+
+    /// ノード対`(source, destination)`の値（呼量または重み）
+    pub fn weight(&self, source: usize, destination: usize) -> f64 {
+        self.weights[source * self.node_num + destination]
+    }
+
+    /// 全ノード対の値の合計
+    pub fn total_weight(&self) -> f64 {
+        self.weights.iter().sum()
+    }
+
+    /// 全ノード対の呼量の合計（アーラン）。値が相対的な重みなら`None`
+    pub fn offered_load(&self) -> Option<f64> {
+        self.offered.then(|| self.total_weight())
+    }
+
+    /// 値の比でノード対を選ぶ
+    ///
+    /// 値が正のノード対が無ければ（ノードが2つ未満の一様な行列など）`None`
+    pub fn sampler(&self) -> Option<PairSampler> {
+        let node_num = self.node_num;
+        if self.uniform {
+            (node_num >= 2).then_some(PairSampler::Uniform { node_num })
+        } else {
+            let index = WeightedIndex::new(&self.weights).ok()?;
+            Some(PairSampler::Weighted { node_num, index })
+        }
+    }
+}
+
+/// [`TrafficMatrix`] の値の比で始点・終点を選ぶ
+#[derive(Clone, Debug)]
+pub enum PairSampler {
+    /// 始点と終点を一様に選び、同じなら終点を選び直す（`node_num`は2以上）
+    Uniform { node_num: usize },
+    /// ノード対を重み付きで選ぶ
+    Weighted {
+        node_num: usize,
+        index: WeightedIndex<f64>,
+    },
+}
+
+impl PairSampler {
+    /// 始点と終点を1組選ぶ
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> (usize, usize) {
+        match self {
+            PairSampler::Uniform { node_num } => {
+                let source = rng.gen_range(0..*node_num);
+                let mut destination = rng.gen_range(0..*node_num);
+                while source == destination {
+                    destination = rng.gen_range(0..*node_num);
+                }
+                (source, destination)
+            }
+            PairSampler::Weighted { node_num, index } => {
+                let pair = index.sample(rng);
+                (pair / node_num, pair % node_num)
+            }
+        }
+    }
+}
+
+/// 組み込みのトラヒック行列の生成方法
+///
+/// 文字列からは `uniform`、`gravity`、`hotspot:ノード,倍率` の形式で作る。
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TrafficModel {
+    Uniform,
+    Gravity,
+    HotSpot { node: usize, factor: f64 },
+}
+
+impl TrafficModel {
+    /// トポロジに合わせたトラヒック行列を作る
+    pub fn build(&self, topology: &Topology) -> TrafficMatrix {
+        match *self {
+            TrafficModel::Uniform => TrafficMatrix::uniform(topology.node_num()),
+            TrafficModel::Gravity => TrafficMatrix::gravity(topology),
+            TrafficModel::HotSpot { node, factor } => {
+                TrafficMatrix::hot_spot(topology.node_num(), node, factor)
+            }
+        }
+    }
+}
+
+impl fmt::Display for TrafficModel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrafficModel::Uniform => write!(f, "uniform"),
+            TrafficModel::Gravity => write!(f, "gravity"),
+            TrafficModel::HotSpot { node, factor } => write!(f, "hotspot:{},{}", node, factor),
+        }
+    }
+}
+
+impl FromStr for TrafficModel {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        match spec.split_once(':') {
+            None if spec == "uniform" => Ok(TrafficModel::Uniform),
+            None if spec == "gravity" => Ok(TrafficModel::Gravity),
+            Some(("hotspot", params)) => {
+                let (node, factor) = params
+                    .split_once(',')
+                    .ok_or_else(|| "hotspot takes `hotspot:node,factor`".to_string())?;
+                let node = node
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("invalid hot-spot node: {}", node))?;
+                let factor = match factor.trim().parse::<f64>() {
+                    Ok(factor) if factor > 0.0 && factor.is_finite() => factor,
+                    _ => return Err(format!("invalid hot-spot factor: {}", factor)),
+                };
+                Ok(TrafficModel::HotSpot { node, factor })
+            }
+            _ => Err(format!(
+                "unknown traffic model `{}` (expected uniform, gravity or hotspot:node,factor)",
+                spec
+            )),
+        }
+    }
+}
+
+/// 呼の種別（クラス）
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TrafficClass {
+    /// 呼が各リンクで使う帯域
+    pub demand: isize,
+    /// 発生する呼のうちこのクラスが占める割合（相対値）
+    pub share: f64,
+}
+
+/// 呼のクラスの構成
+///
+/// 文字列からは `帯域:割合` をカンマで区切って作る（例: `1:0.7,2:0.2,6:0.1`）。
+/// 割合を省略したクラスは割合1とする。既定は帯域1のクラスだけ。
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrafficClasses {
+    classes: Vec<TrafficClass>,
+}
+
+impl TrafficClasses {
+    /// クラスの一覧から作る。帯域はすべて正、割合の合計は正であること
+    pub fn new(classes: Vec<TrafficClass>) -> Result<Self, String> {
+        if classes.is_empty() {
+            return Err("at least one traffic class is required".to_string());
+        }
+        if let Some(class) = classes.iter().find(|class| class.demand < 1) {
+            return Err(format!("class demand must be positive: {}", class.demand));
+        }
+        if let Some(class) = classes
+            .iter()
+            .find(|class| !(class.share >= 0.0 && class.share.is_finite()))
+        {
+            return Err(format!("invalid class share: {}", class.share));
+        }
+        if classes.iter().map(|class| class.share).sum::<f64>() <= 0.0 {
+            return Err("total class share must be positive".to_string());
+        }
+        Ok(TrafficClasses { classes })
+    }
+
+    /// クラスの数
+    pub fn len(&self) -> usize {
+        self.classes.len()
+    }
+
+    /// クラスが1つも無いか（[`TrafficClasses::new`]で作った値では常に`false`）
+    pub fn is_empty(&self) -> bool {
+        self.classes.is_empty()
+    }
+
+    /// クラス`class`の呼の帯域
+    pub fn demand(&self, class: usize) -> isize {
+        self.classes[class].demand
+    }
+
+    /// クラスの一覧
+    pub fn classes(&self) -> &[TrafficClass] {
+        &self.classes
+    }
+
+    /// 割合に従ってクラスを選ぶ
+    pub fn sampler(&self) -> ClassSampler {
+        if self.classes.len() == 1 {
+            ClassSampler(None)
+        } else {
+            let shares = self.classes.iter().map(|class| class.share);
+            ClassSampler(Some(
+                WeightedIndex::new(shares).expect("total share must be positive"),
+            ))
+        }
+    }
+}
+
+impl Default for TrafficClasses {
+    /// 帯域1の呼だけ
+    fn default() -> Self {
+        TrafficClasses {
+            classes: vec![TrafficClass {
+                demand: 1,
+                share: 1.0,
+            }],
+        }
+    }
+}
+
+/// [`TrafficClasses`] の割合でクラスを選ぶ
+#[derive(Clone, Debug)]
+pub struct ClassSampler(Option<WeightedIndex<f64>>);
+
+impl ClassSampler {
+    /// クラスを1つ選ぶ（クラスが1つなら乱数を使わない）
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> usize {
+        match &self.0 {
+            Some(index) => index.sample(rng),
+            None => 0,
+        }
+    }
+}
+
+impl fmt::Display for TrafficClasses {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let classes: Vec<String> = self
+            .classes
+            .iter()
+            .map(|class| format!("{}:{}", class.demand, class.share))
+            .collect();
+        write!(f, "{}", classes.join(","))
+    }
+}
+
+impl FromStr for TrafficClasses {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let classes = spec
+            .split(',')
+            .map(|class| {
+                let (demand, share) = match class.split_once(':') {
+                    Some((demand, share)) => (demand, Some(share)),
+                    None => (class, None),
+                };
+                let demand = demand
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("invalid class demand: {}", demand))?;
+                let share = match share {
+                    Some(share) => share
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("invalid class share: {}", share))?,
+                    None => 1.0,
+                };
+                Ok(TrafficClass { demand, share })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        TrafficClasses::new(classes)
+    }
+}
+
+/// トラヒック行列ファイルの読み込みエラー
+#[derive(Debug)]
+pub enum TrafficError {
+    /// ファイルが読めない
+    Io(io::Error),
+    /// 内容が不正（行・桁は1始まり）
+    Parse {
+        line: usize,
+        column: usize,
+        kind: ParseErrorKind,
+    },
+    /// 呼量がすべて0
+    NoTraffic,
+}
+
+impl fmt::Display for TrafficError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrafficError::Io(e) => write!(f, "{}", e),
+            TrafficError::Parse { line, column, kind } => {
+                write!(f, "line {}, column {}: {}", line, column, kind)
+            }
+            TrafficError::NoTraffic => write!(f, "traffic matrix has no positive load"),
+        }
+    }
+}
+
+impl std::error::Error for TrafficError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TrafficError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for TrafficError {
+    fn from(e: io::Error) -> Self {
+        TrafficError::Io(e)
+    }
+}
@@ -0,0 +1,72 @@
+use routing_sim::analytic::{self, erlang_b, kaufman_roberts};
+use routing_sim::router;
+use routing_sim::traffic::TrafficClasses;
+use routing_sim::{Topology, TrafficMatrix};
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "{} is not within {} of {}",
+        actual,
+        tolerance,
+        expected
+    );
+}
+
+#[test]
+fn erlang_b_matches_known_values() {
+    assert_close(erlang_b(10.0, 10), 0.21458, 5e-6);
+    assert_close(erlang_b(1.0, 1), 0.5, 1e-12);
+    assert_close(erlang_b(2.0, 5), 0.03670, 5e-6);
+    assert_eq!(erlang_b(3.0, 0), 1.0);
+    assert_eq!(erlang_b(0.0, 4), 0.0);
+}
+
+#[test]
+fn kaufman_roberts_matches_the_product_form() {
+    // 容量2に帯域1と帯域2の呼がそれぞれ1アーラン：
+    // 状態(帯域1の呼数, 帯域2の呼数)の重みは(0,0):1, (1,0):1, (2,0):1/2, (0,1):1
+    let blocking = kaufman_roberts(2, &[(1, 1.0), (2, 1.0)]);
+    assert_close(blocking[0], 1.5 / 3.5, 1e-12);
+    assert_close(blocking[1], 2.5 / 3.5, 1e-12);
+
+    // クラスが1つならErlang-B式と同じ
+    assert_close(
+        kaufman_roberts(10, &[(1, 10.0)])[0],
+        erlang_b(10.0, 10),
+        1e-12,
+    );
+    assert_close(kaufman_roberts(7, &[(2, 1.5)])[0], erlang_b(1.5, 3), 1e-12);
+    // 容量を超える帯域の呼は必ず呼損になる
+    assert_eq!(kaufman_roberts(2, &[(1, 1.0), (3, 1.0)])[1], 1.0);
+}
+
+#[test]
+fn single_link_estimate_is_erlang_b() {
+    let t = Topology::from_reader("0 1 1 5\n".as_bytes()).unwrap();
+    let mut router = router::build("shortest-path", &t).unwrap();
+    let routes = analytic::routes(&t, router.as_mut(), &TrafficMatrix::uniform(2), 4.0);
+    let estimate = analytic::reduced_load(&t, &routes, &TrafficClasses::default());
+    assert!(estimate.converged);
+    assert_close(estimate.blocking, erlang_b(4.0, 5), 1e-12);
+}
+
+#[test]
+fn tandem_fixed_point_converges() {
+    // 0-1-2の直線。各ノード対に2アーラン、0と2の間の呼は両方のリンクを通る
+    let t = Topology::from_reader("0 1 1 5\n1 2 1 5\n".as_bytes()).unwrap();
+    let mut router = router::build("shortest-path", &t).unwrap();
+    let routes = analytic::routes(&t, router.as_mut(), &TrafficMatrix::uniform(3), 12.0);
+    assert_eq!(routes.len(), 6);
+    let estimate = analytic::reduced_load(&t, &routes, &TrafficClasses::default());
+    assert!(estimate.converged);
+
+    // 対称なので両リンクの呼損率は等しく、B = E(4 + 4(1 - B), 5)を満たす
+    let b = estimate.link_blocking[0][0];
+    assert_close(estimate.link_blocking[1][0], b, 1e-12);
+    assert_close(b, erlang_b(4.0 + 4.0 * (1.0 - b), 5), 1e-9);
+    assert!(b > erlang_b(4.0, 5) && b < erlang_b(8.0, 5));
+    // 1ホップの呼8アーランは呼損率B、2ホップの呼4アーランは1 - (1 - B)^2
+    let expected = (8.0 * b + 4.0 * (1.0 - (1.0 - b).powi(2))) / 12.0;
+    assert_close(estimate.blocking, expected, 1e-12);
+}
@@ -0,0 +1,118 @@
+use routing_sim::rng;
+use routing_sim::router;
+use routing_sim::traffic::{TrafficClass, TrafficClasses};
+use routing_sim::{Call, Simulator, Topology};
+
+fn call(time: f64, holding: f64, class: usize, demand: isize) -> Call {
+    Call {
+        time,
+        source: 0,
+        destination: 1,
+        holding,
+        class,
+        demand,
+    }
+}
+
+/// 帯域の異なる呼が空き容量を分け合い、終了時に自分の帯域だけを解放する
+#[test]
+fn multi_rate_calls_take_and_release_their_demand() {
+    let t = Topology::from_reader("0 1 1 3\n".as_bytes()).unwrap();
+    let classes: TrafficClasses = "1:0.5,2:0.5".parse().unwrap();
+    let calls = [
+        call(0.0, 2.0, 1, 2), // 空き容量3 → 1
+        call(1.0, 1.0, 1, 2), // 空き容量1なので呼損
+        call(1.5, 1.0, 0, 1), // 空き容量1 → 0
+        call(2.0, 2.0, 1, 2), // 時刻2に1つ目が終了して2 → 0
+        call(2.5, 1.0, 0, 1), // 時刻2.5に3つ目が終了して1 → 0
+        call(3.0, 1.0, 0, 1), // 空き容量0なので呼損
+    ];
+    let mut router = router::build("shortest-path", &t).unwrap();
+    let result = Simulator::new(&t, calls.len())
+        .with_classes(classes)
+        .run_calls(router.as_mut(), &calls, 0);
+
+    assert_eq!((result.total_attempts, result.total_success), (6, 4));
+    assert_eq!(result.classes.len(), 2);
+    assert_eq!(result.classes[0].total_attempts, 3);
+    assert_eq!(result.classes[0].total_success, 2);
+    assert_eq!(result.classes[1].total_attempts, 3);
+    assert_eq!(result.classes[1].total_success, 2);
+    assert_eq!(result.classes[1].call_loss_rate(), 1.0 / 3.0);
+    // 使用帯域は[0, 1.5)で2、[1.5, 3]で3
+    assert_eq!(result.links[0].occupancy, 7.5);
+    assert_eq!(result.links[0].blocked, 2);
+}
+
+/// 呼が発生しなかったクラスは呼損も無いので呼損率0
+#[test]
+fn classes_without_calls_have_zero_loss_rate() {
+    let t = Topology::from_reader("0 1 1 3\n".as_bytes()).unwrap();
+    let mut router = router::build("shortest-path", &t).unwrap();
+    let result = Simulator::new(&t, 1)
+        .with_classes("1,6".parse().unwrap())
+        .run_calls(router.as_mut(), &[call(0.0, 1.0, 0, 1)], 0);
+    assert_eq!(result.classes[0].call_loss_rate(), 0.0);
+    assert_eq!(result.classes[1].total_attempts, 0);
+    assert_eq!(result.classes[1].call_loss_rate(), 0.0);
+}
+
+/// 発生させる呼はクラスの帯域を使い、クラスは割合に従って選ばれる
+#[test]
+fn generated_calls_follow_the_class_shares() {
+    let t = Topology::from_reader("0 1 1 3\n1 2 1 3\n".as_bytes()).unwrap();
+    let classes: TrafficClasses = "1:3,4:1".parse().unwrap();
+    let calls = Simulator::new(&t, 20_000)
+        .with_classes(classes.clone())
+        .generate_calls(5, &mut rng::stream(2, 0, 0));
+    for call in &calls {
+        assert_eq!(call.demand, classes.demand(call.class));
+    }
+    let wide = calls.iter().filter(|call| call.class == 1).count();
+    let share = wide as f64 / calls.len() as f64;
+    assert!((share - 0.25).abs() < 0.01, "{}", share);
+}
+
+#[test]
+fn class_specs_are_parsed() {
+    let classes: TrafficClasses = "1:0.7, 2:0.2,6".parse().unwrap();
+    assert_eq!(
+        classes.classes(),
+        [
+            TrafficClass {
+                demand: 1,
+                share: 0.7
+            },
+            TrafficClass {
+                demand: 2,
+                share: 0.2
+            },
+            TrafficClass {
+                demand: 6,
+                share: 1.0
+            },
+        ]
+    );
+    assert_eq!(classes.to_string(), "1:0.7,2:0.2,6:1");
+    assert_eq!(TrafficClasses::default().to_string(), "1:1");
+}
+
+#[test]
+fn malformed_class_specs_are_rejected() {
+    let error = |spec: &str| spec.parse::<TrafficClasses>().unwrap_err();
+    // 割合が数値でない・負・有限でない
+    assert_eq!(error("1:x"), "invalid class share: x");
+    assert_eq!(error("1:0.5,2:-0.5"), "invalid class share: -0.5");
+    assert_eq!(error("1:inf"), "invalid class share: inf");
+    // 帯域が正の整数でない
+    assert_eq!(error("0:1"), "class demand must be positive: 0");
+    assert_eq!(error("-2:1"), "class demand must be positive: -2");
+    assert_eq!(error("1.5:1"), "invalid class demand: 1.5");
+    assert_eq!(error(""), "invalid class demand: ");
+    // 割合の合計が正にならない
+    assert_eq!(error("1:0,2:0"), "total class share must be positive");
+    assert_eq!(
+        TrafficClasses::new(Vec::new()).unwrap_err(),
+        "at least one traffic class is required"
+    );
+}
@@ -0,0 +1,23 @@
+//! 結合テストで共有する呼とトポロジの組み立て
+
+// テストごとに使う関数が違うので、使わない関数があっても警告しない
+#![allow(dead_code)]
+
+use routing_sim::{Call, Topology};
+
+/// 帯域1・クラス0の呼
+pub fn call(time: f64, source: usize, destination: usize, holding: f64) -> Call {
+    Call {
+        time,
+        source,
+        destination,
+        holding,
+        class: 0,
+        demand: 1,
+    }
+}
+
+/// 0-1-2の直線（距離1・容量1）
+pub fn line() -> Topology {
+    Topology::from_reader("0 1 1 1\n1 2 1 1\n".as_bytes()).unwrap()
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use rand::Rng;
+use routing_sim::process::{ArrivalProcess, HoldingTime};
+use routing_sim::rng;
+use routing_sim::router::{self, Router};
+use routing_sim::{RunResult, Simulator, Topology};
+
+/// 方式名と初期化のシードごとの、方式に渡された呼（始点, 終点, 帯域）の列
+type Log = Arc<Mutex<BTreeMap<(&'static str, u64), Vec<(usize, usize, isize)>>>>;
+
+/// 渡された呼を記録しながら別の方式に経路を求めさせる方式
+struct Recorder {
+    inner: Box<dyn Router>,
+    seed: u64,
+    log: Log,
+}
+
+impl Clone for Recorder {
+    fn clone(&self) -> Self {
+        Recorder {
+            inner: self.inner.clone_box(),
+            seed: self.seed,
+            log: Arc::clone(&self.log),
+        }
+    }
+}
+
+impl Router for Recorder {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn find_path(
+        &mut self,
+        source: usize,
+        destination: usize,
+        bandwidth: &[isize],
+        demand: isize,
+    ) -> Option<Vec<usize>> {
+        self.log
+            .lock()
+            .unwrap()
+            .entry((self.name(), self.seed))
+            .or_default()
+            .push((source, destination, demand));
+        self.inner.find_path(source, destination, bandwidth, demand)
+    }
+
+    fn reset(&mut self, seed: u64) {
+        self.seed = seed;
+        self.inner.reset(seed);
+    }
+
+    fn blocked(&mut self, source: usize, destination: usize) {
+        self.inner.blocked(source, destination);
+    }
+}
+
+/// 共通乱数法では、どの方式にも実行ごとに同じ呼の列が同じ順に渡される
+#[test]
+fn every_strategy_receives_the_same_calls() {
+    let t = Topology::load(concat!(env!("CARGO_MANIFEST_DIR"), "/../distance.txt")).unwrap();
+    let simulator = Simulator::new(&t, 500)
+        .with_arrival(ArrivalProcess::Poisson { rate: 1.0 })
+        .with_holding(HoldingTime::Exponential)
+        .with_classes("1:0.8,3:0.2".parse().unwrap());
+    let log = Log::default();
+    let names = ["shortest-path", "least-loaded", "dynamic-alternative"];
+    let routers: Vec<Box<dyn Router>> = names
+        .iter()
+        .map(|name| {
+            Box::new(Recorder {
+                inner: router::build(name, &t).unwrap(),
+                seed: 0,
+                log: Arc::clone(&log),
+            }) as Box<dyn Router>
+        })
+        .collect();
+    let (n, runs, seed) = (20, 4, 9);
+    let results = simulator.compare(&routers, n, runs, seed);
+    assert_eq!(results.len(), runs);
+
+    let log = log.lock().unwrap();
+    assert_eq!(log.len(), names.len() * runs);
+    for (run, result) in results.iter().enumerate() {
+        // 実行`run`の呼の列と方式の初期化のシードを作り直す
+        let mut rng = rng::stream(seed, n as u64, run as u64);
+        let calls: Vec<_> = simulator
+            .generate_calls(n, &mut rng)
+            .iter()
+            .map(|call| (call.source, call.destination, call.demand))
+            .collect();
+        let router_seed: u64 = rng.gen();
+        for (name, result) in names.iter().zip(result) {
+            assert_eq!(log[&(*name, router_seed)], calls, "{} run {}", name, run);
+            assert_eq!(result.total_attempts, calls.len());
+        }
+        // 呼の列が同じなので、ノード対ごとの呼の数も方式によらない
+        for result in &result[1..] {
+            let attempts = |result: &RunResult| {
+                result
+                    .pairs
+                    .iter()
+                    .map(|(&pair, pair_result)| (pair, pair_result.total_attempts))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(attempts(result), attempts(&results[run][0]));
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use routing_sim::router::{DynamicAlternative, Router};
+use routing_sim::Topology;
+
+/// 0-1の直接のリンク（距離1）と、2を経由する迂回経路（距離2）の三角形
+fn triangle() -> Topology {
+    Topology::from_reader("0 1 1 1\n0 2 1 1\n2 1 1 1\n".as_bytes()).unwrap()
+}
+
+/// 三角形に3を経由する迂回経路（距離3）を加え、0→1の迂回経路を2本にしたもの
+fn two_triangles() -> Topology {
+    Topology::from_reader("0 1 1 1\n0 2 1 1\n2 1 1 1\n0 3 1 1\n3 1 2 1\n".as_bytes()).unwrap()
+}
+
+#[test]
+fn direct_path_is_used_while_it_has_capacity() {
+    let t = triangle();
+    let mut router = DynamicAlternative::new(&t, 3);
+    router.reset(1);
+    let mut bandwidth = t.capacities();
+    assert_eq!(router.find_path(0, 1, &bandwidth, 1), Some(vec![0, 1]));
+
+    // 直接のリンクが塞がれば迂回経路を使い、迂回経路も塞がれば呼損
+    bandwidth[t.link_between(0, 1).unwrap()] = 0;
+    for _ in 0..3 {
+        assert_eq!(router.find_path(0, 1, &bandwidth, 1), Some(vec![0, 2, 1]));
+    }
+    bandwidth[t.link_between(2, 1).unwrap()] = 0;
+    assert_eq!(router.find_path(0, 1, &bandwidth, 1), None);
+}
+
+/// 迂回経路は呼が通る限り変わらず、呼損の後にだけ選び直す
+#[test]
+fn sticky_alternate_is_redrawn_only_after_a_block() {
+    let t = two_triangles();
+    let direct = t.link_between(0, 1).unwrap();
+    let via = |node: usize| {
+        [
+            t.link_between(0, node).unwrap(),
+            t.link_between(node, 1).unwrap(),
+        ]
+    };
+    let mut router = DynamicAlternative::new(&t, 3);
+    router.reset(7);
+
+    // 直接のリンクだけを塞いだ状態
+    let mut open = t.capacities();
+    open[direct] = 0;
+    let mut current = router.find_path(0, 1, &open, 1).unwrap();
+    let mut redraws = Vec::new();
+    for _ in 0..20 {
+        // 呼が通る間は同じ迂回経路を使い続ける
+        for _ in 0..5 {
+            assert_eq!(router.find_path(0, 1, &open, 1).as_ref(), Some(&current));
+        }
+        // 現在の迂回経路を塞ぐと呼損になる（もう一方の迂回経路は試さない）
+        let mut bandwidth = open.clone();
+        for link in via(current[1]) {
+            bandwidth[link] = 0;
+        }
+        assert_eq!(router.find_path(0, 1, &bandwidth, 1), None);
+        assert_eq!(router.find_path(0, 1, &open, 1).as_ref(), Some(&current));
+
+        router.blocked(0, 1);
+        let next = router.find_path(0, 1, &open, 1).unwrap();
+        assert!(next == [0, 2, 1] || next == [0, 3, 1], "{:?}", next);
+        redraws.push(next.clone());
+        current = next;
+    }
+    // 選び直しでは両方の迂回経路が選ばれる
+    assert!(redraws.iter().any(|path| path[1] == 2));
+    assert!(redraws.iter().any(|path| path[1] == 3));
+
+    // 同じシードで初期化すれば同じ順に選ぶ
+    router.reset(7);
+    let first = router.find_path(0, 1, &open, 1).unwrap();
+    let mut replay = Vec::new();
+    for _ in 0..20 {
+        router.blocked(0, 1);
+        replay.push(router.find_path(0, 1, &open, 1).unwrap());
+    }
+    assert_eq!(replay, redraws);
+    assert!(first == [0, 2, 1] || first == [0, 3, 1]);
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use common::call;
+use routing_sim::fairness::PairStats;
+use routing_sim::router;
+use routing_sim::stats::jain_index;
+use routing_sim::{PairResult, RunResult, Simulator};
+
+fn pair(total_attempts: usize, total_success: usize) -> PairResult {
+    PairResult {
+        total_attempts,
+        total_success,
+    }
+}
+
+#[test]
+fn jain_index_ranges_from_one_over_n_to_one() {
+    assert_eq!(jain_index(&[0.5, 0.5, 0.5]), 1.0);
+    assert_eq!(jain_index(&[1.0, 0.0, 0.0, 0.0]), 0.25);
+    assert_eq!(jain_index(&[0.0, 0.0]), 1.0);
+    assert!((jain_index(&[1.0, 0.5]) - 0.9).abs() < 1e-12);
+    assert!(jain_index(&[]).is_nan());
+}
+
+#[test]
+fn pairs_are_pooled_over_runs_and_stratified_by_hops() {
+    // 0-1-2の直線
+    let t = common::line();
+    let run = |pairs: &[((usize, usize), PairResult)]| RunResult {
+        pairs: pairs.iter().copied().collect(),
+        ..RunResult::default()
+    };
+    let results = [
+        run(&[((0, 1), pair(4, 4)), ((0, 2), pair(2, 1))]),
+        run(&[((0, 2), pair(2, 0)), ((2, 1), pair(4, 2))]),
+    ];
+    let stats = PairStats::new(&t, &results);
+
+    let pairs: Vec<((usize, usize), PairResult)> = stats
+        .pairs()
+        .map(|(pair, result)| (pair, *result))
+        .collect();
+    assert_eq!(
+        pairs,
+        [
+            ((0, 1), pair(4, 4)),
+            ((0, 2), pair(4, 1)),
+            ((2, 1), pair(4, 2))
+        ]
+    );
+    assert_eq!(stats.hops(0, 2), 2);
+
+    let strata = stats.by_hops();
+    assert_eq!(strata.len(), 2);
+    assert_eq!((strata[0].hops, strata[0].pairs), (1, 2));
+    assert_eq!(strata[0].call_loss_rate(), 2.0 / 8.0);
+    assert_eq!((strata[1].hops, strata[1].pairs), (2, 1));
+    assert_eq!(strata[1].call_loss_rate(), 3.0 / 4.0);
+    assert_eq!(stats.stratum(2), Some(strata[1]));
+    assert_eq!(stats.stratum(3), None);
+
+    // 確立率1, 0.25, 0.5
+    assert_eq!(stats.jain_index(), jain_index(&[1.0, 0.25, 0.5]));
+}
+
+#[test]
+fn simulator_counts_calls_per_ordered_pair() {
+    let t = common::line();
+    let calls = [
+        call(0.0, 0, 2, 10.0),
+        call(1.0, 0, 1, 10.0),
+        call(2.0, 1, 0, 10.0),
+        call(3.0, 0, 2, 10.0),
+    ];
+    let mut router = router::build("shortest-path", &t).unwrap();
+    let result = Simulator::new(&t, calls.len()).run_calls(router.as_mut(), &calls, 0);
+    assert_eq!(result.pairs.len(), 3);
+    assert_eq!(result.pairs[&(0, 2)], pair(2, 1));
+    assert_eq!(result.pairs[&(0, 1)], pair(1, 0));
+    assert_eq!(result.pairs[&(1, 0)], pair(1, 0));
+    assert_eq!(result.pairs[&(0, 2)].call_loss_rate(), 0.5);
+    assert_eq!(pair(0, 0).call_loss_rate(), 0.0);
+}
@@ -0,0 +1,173 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use routing_sim::process::ArrivalProcess;
+use routing_sim::router::{
+    self, k_shortest_paths, DynamicAlternative, KShortestPath, LeastLoaded, Router,
+};
+use routing_sim::topology::{Link, Topology};
+use routing_sim::Simulator;
+
+/// Yenの論文などで使われる6ノードの例（リンクは双方向）
+fn yen_example() -> Topology {
+    Topology::from_reader(
+        "0 1 3 1\n0 2 2 1\n1 3 4 1\n2 1 1 1\n2 3 2 1\n\
+         2 4 3 1\n3 4 2 1\n3 5 1 1\n4 5 2 1\n"
+            .as_bytes(),
+    )
+    .unwrap()
+}
+
+/// 全てのループの無い経路（距離, ノード列）を距離・ノード列の順に並べる
+fn all_simple_paths(
+    topology: &Topology,
+    source: usize,
+    destination: usize,
+) -> Vec<(isize, Vec<usize>)> {
+    fn visit(
+        topology: &Topology,
+        destination: usize,
+        path: &mut Vec<usize>,
+        found: &mut Vec<(isize, Vec<usize>)>,
+    ) {
+        let node = *path.last().unwrap();
+        if node == destination {
+            found.push((topology.path_distance(path), path.clone()));
+            return;
+        }
+        for adjacent in topology.neighbors(node) {
+            if !path.contains(&adjacent.node) {
+                path.push(adjacent.node);
+                visit(topology, destination, path, found);
+                path.pop();
+            }
+        }
+    }
+    let mut found = Vec::new();
+    visit(topology, destination, &mut vec![source], &mut found);
+    found.sort();
+    found
+}
+
+#[test]
+fn paths_come_in_order_of_distance_then_node_sequence() {
+    let t = yen_example();
+    assert_eq!(
+        k_shortest_paths(&t, 0, 5, 6),
+        [
+            vec![0, 2, 3, 5],
+            vec![0, 1, 2, 3, 5],
+            vec![0, 2, 4, 5],
+            vec![0, 1, 3, 5],
+            vec![0, 2, 1, 3, 5],
+            vec![0, 2, 3, 4, 5],
+        ]
+    );
+    // 経路がk本に満たなければあるだけ返す
+    let line = Topology::from_reader("0 1 1 1\n1 2 1 1\n".as_bytes()).unwrap();
+    assert_eq!(k_shortest_paths(&line, 0, 2, 3), [vec![0, 1, 2]]);
+}
+
+#[test]
+fn matches_enumeration_of_loopless_paths_on_random_graphs() {
+    let mut rng = ChaCha8Rng::seed_from_u64(13);
+    for _ in 0..30 {
+        let node_num = 6;
+        let mut links = Vec::new();
+        for node1 in 0..node_num {
+            for node2 in node1 + 1..node_num {
+                if rng.gen_bool(0.5) {
+                    links.push(Link {
+                        node1,
+                        node2,
+                        distance: rng.gen_range(1..4),
+                        capacity: 1,
+                    });
+                }
+            }
+        }
+        let t = Topology::new(node_num, links);
+        for source in 0..node_num {
+            for destination in (0..node_num).filter(|&d| d != source) {
+                let all = all_simple_paths(&t, source, destination);
+                let paths = k_shortest_paths(&t, source, destination, 4);
+                // 距離は全経路の短い方から4本と同じ（同じ距離の経路のどれを選ぶかは規則による）
+                let distances: Vec<isize> = paths.iter().map(|p| t.path_distance(p)).collect();
+                let expected: Vec<isize> = all.iter().take(4).map(|&(d, _)| d).collect();
+                assert_eq!(distances, expected, "{} -> {}", source, destination);
+                for (i, path) in paths.iter().enumerate() {
+                    assert!(
+                        all.iter().any(|(_, p)| p == path),
+                        "{:?} is not a loopless path",
+                        path
+                    );
+                    assert!(!paths[..i].contains(path), "{:?} appears twice", path);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn router_falls_back_to_the_next_candidate() {
+    let t = yen_example();
+    let mut router = router::build("k-shortest-path", &t).unwrap();
+    let mut bandwidth = t.capacities();
+    assert_eq!(
+        router.find_path(0, 5, &bandwidth, 1),
+        Some(vec![0, 2, 3, 5])
+    );
+    // 3-5を塞ぐと2本目（0-1-2-3-5）も使えず、3本目の0-2-4-5を使う
+    bandwidth[t.link_between(3, 5).unwrap()] = 0;
+    assert_eq!(
+        router.find_path(0, 5, &bandwidth, 1),
+        Some(vec![0, 2, 4, 5])
+    );
+    // 4-5も塞ぐと候補（k = 3）が無くなる
+    bandwidth[t.link_between(4, 5).unwrap()] = 0;
+    assert_eq!(router.find_path(0, 5, &bandwidth, 1), None);
+}
+
+/// 複製した方式は候補の表を共有し、求め直さない
+#[test]
+fn cloned_routers_share_the_candidate_table() {
+    let t = yen_example();
+    let bandwidth = t.capacities();
+    let router = KShortestPath::new(&t, 3);
+    let mut first = router.clone();
+    first.find_path(0, 5, &bandwidth, 1);
+    assert_eq!(router.computed_pairs(), 1);
+
+    let mut second = first.clone();
+    assert_eq!(second.computed_pairs(), 1);
+    second.find_path(0, 5, &bandwidth, 1);
+    assert_eq!(router.computed_pairs(), 1);
+    second.find_path(1, 4, &bandwidth, 1);
+    assert_eq!(first.computed_pairs(), 2);
+}
+
+/// 実行ごとに方式を複製しても、各ノード対の候補は全実行で1回だけ求める
+#[test]
+fn replications_compute_each_pair_once() {
+    let t = Topology::load(concat!(env!("CARGO_MANIFEST_DIR"), "/../distance.txt")).unwrap();
+    let simulator = Simulator::new(&t, 300).with_arrival(ArrivalProcess::Poisson { rate: 1.0 });
+    let ksp = KShortestPath::new(&t, 3);
+    let least_loaded = LeastLoaded::new(&t, 3);
+    let dar = DynamicAlternative::new(&t, 3);
+    let counts = [
+        (simulator.replicate(&ksp, 5, 6, 1), ksp.computed_pairs()),
+        (
+            simulator.replicate(&least_loaded, 5, 6, 1),
+            least_loaded.computed_pairs(),
+        ),
+        (simulator.replicate(&dar, 5, 6, 1), dar.computed_pairs()),
+    ];
+    for (results, computed) in counts {
+        let mut pairs: Vec<(usize, usize)> = results
+            .iter()
+            .flat_map(|result| result.pairs.keys().copied())
+            .collect();
+        pairs.sort_unstable();
+        pairs.dedup();
+        assert_eq!(computed, pairs.len());
+    }
+}
@@ -0,0 +1,78 @@
+use routing_sim::router::{LeastLoaded, Router};
+use routing_sim::Topology;
+
+/// 0→1の候補経路が距離の短い順に 0-1、0-2-1、0-3-1、0-4-5-1 になるトポロジ
+fn topology() -> Topology {
+    Topology::from_reader(
+        "0 1 1 9\n0 2 1 9\n2 1 1 9\n0 3 1 9\n3 1 2 9\n0 4 1 9\n4 5 1 9\n5 1 2 9\n".as_bytes(),
+    )
+    .unwrap()
+}
+
+/// 経路`path`上の全リンクの空き容量を`free`にする
+fn set_free(t: &Topology, bandwidth: &mut [isize], path: &[usize], free: isize) {
+    for link in t.path_links(path).unwrap() {
+        bandwidth[link] = free;
+    }
+}
+
+#[test]
+fn candidate_with_the_widest_bottleneck_is_chosen() {
+    let t = topology();
+    let mut router = LeastLoaded::new(&t, 4);
+    let mut bandwidth = t.capacities();
+    set_free(&t, &mut bandwidth, &[0, 1], 2);
+    set_free(&t, &mut bandwidth, &[0, 2, 1], 3);
+    set_free(&t, &mut bandwidth, &[0, 3, 1], 4);
+    set_free(&t, &mut bandwidth, &[0, 4, 5, 1], 8);
+    // 経路の途中のリンクだけが狭ければ、そのリンクがボトルネックになる
+    bandwidth[t.link_between(4, 5).unwrap()] = 1;
+    assert_eq!(router.find_path(0, 1, &bandwidth, 1), Some(vec![0, 3, 1]));
+
+    bandwidth[t.link_between(4, 5).unwrap()] = 5;
+    assert_eq!(
+        router.find_path(0, 1, &bandwidth, 1),
+        Some(vec![0, 4, 5, 1])
+    );
+}
+
+#[test]
+fn ties_are_broken_by_hop_count_then_distance() {
+    let t = topology();
+    let mut router = LeastLoaded::new(&t, 4);
+    let mut bandwidth = t.capacities();
+
+    // 全経路の空き容量が同じなら、ホップ数の少ない直接のリンク
+    assert_eq!(router.find_path(0, 1, &bandwidth, 1), Some(vec![0, 1]));
+
+    // 2ホップの経路どうしなら距離の短い方、3ホップの経路より優先する
+    set_free(&t, &mut bandwidth, &[0, 1], 1);
+    assert_eq!(router.find_path(0, 1, &bandwidth, 1), Some(vec![0, 2, 1]));
+    set_free(&t, &mut bandwidth, &[0, 2, 1], 1);
+    assert_eq!(router.find_path(0, 1, &bandwidth, 1), Some(vec![0, 3, 1]));
+    set_free(&t, &mut bandwidth, &[0, 3, 1], 1);
+    assert_eq!(
+        router.find_path(0, 1, &bandwidth, 1),
+        Some(vec![0, 4, 5, 1])
+    );
+}
+
+#[test]
+fn candidates_below_the_demand_are_skipped() {
+    let t = topology();
+    let mut router = LeastLoaded::new(&t, 4);
+    let mut bandwidth = t.capacities();
+    set_free(&t, &mut bandwidth, &[0, 1], 1);
+    set_free(&t, &mut bandwidth, &[0, 2, 1], 2);
+    set_free(&t, &mut bandwidth, &[0, 3, 1], 0);
+    set_free(&t, &mut bandwidth, &[0, 4, 5, 1], 3);
+
+    assert_eq!(
+        router.find_path(0, 1, &bandwidth, 3),
+        Some(vec![0, 4, 5, 1])
+    );
+    set_free(&t, &mut bandwidth, &[0, 4, 5, 1], 2);
+    // 帯域2では0-2-1と0-4-5-1が同じ空き容量になり、ホップ数の少ない方を選ぶ
+    assert_eq!(router.find_path(0, 1, &bandwidth, 2), Some(vec![0, 2, 1]));
+    assert_eq!(router.find_path(0, 1, &bandwidth, 3), None);
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use common::call;
+use routing_sim::router;
+use routing_sim::{Call, Simulator, Topology, Warmup};
+
+/// 0-1-2の直線（容量1）で、最初の呼が両方のリンクを塞ぐ
+fn calls() -> Vec<Call> {
+    vec![
+        call(0.0, 0, 2, 10.0),
+        call(1.0, 0, 1, 1.0),
+        call(2.0, 1, 2, 1.0),
+        call(4.0, 0, 2, 1.0),
+    ]
+}
+
+#[test]
+fn occupancy_and_bottlenecks_are_counted_per_link() {
+    let t = common::line();
+    // 経路を返して受け付けられない方式と、経路を返さない方式のどちらでも数える
+    for name in ["shortest-path", "shortest-path-on-demand"] {
+        let mut router = router::build(name, &t).unwrap();
+        let result = Simulator::new(&t, 4).run_calls(router.as_mut(), &calls(), 0);
+        assert_eq!(result.total_blocked(), 3);
+        assert_eq!(result.duration, 4.0);
+        for link in 0..2 {
+            assert_eq!(result.links[link].occupancy, 4.0);
+            assert_eq!(result.utilization(link, 1), 1.0);
+            assert_eq!(result.links[link].blocked, 2, "{} link {}", name, link);
+        }
+    }
+}
+
+#[test]
+fn link_statistics_skip_the_warm_up() {
+    let t = common::line();
+    let mut router = router::build("shortest-path", &t).unwrap();
+    let result = Simulator::new(&t, 4)
+        .with_warmup(Warmup::Calls(2))
+        .run_calls(router.as_mut(), &calls(), 0);
+    assert_eq!(result.duration, 2.0);
+    assert_eq!(result.mean_occupancy(0), 1.0);
+    assert_eq!(result.links[0].blocked, 1);
+    assert_eq!(result.links[1].blocked, 2);
+}
+
+#[test]
+fn released_capacity_lowers_the_utilization() {
+    let t = Topology::from_reader("0 1 1 2\n".as_bytes()).unwrap();
+    let mut router = router::build("shortest-path", &t).unwrap();
+    // 帯域1の呼が[0, 2)と[1, 4)に使う。期間は[0, 4]
+    let calls = [
+        call(0.0, 0, 1, 2.0),
+        call(1.0, 1, 0, 3.0),
+        call(4.0, 0, 1, 1.0),
+    ];
+    let result = Simulator::new(&t, 3).run_calls(router.as_mut(), &calls, 0);
+    assert_eq!(result.total_blocked(), 0);
+    assert_eq!(result.links[0].occupancy, 5.0);
+    assert_eq!(result.utilization(0, 2), 5.0 / 8.0);
+}
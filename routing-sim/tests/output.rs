@@ -0,0 +1,120 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use routing_sim::output::{Column, ColumnType, Format, Metadata, ResultWriter, Value};
+use routing_sim::Topology;
+
+/// テストごとの空の作業ディレクトリ
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("routing-sim-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn metadata() -> Metadata {
+    Metadata {
+        strategy: "least-loaded".to_string(),
+        topology: "distance.txt".to_string(),
+        topology_hash: 0x1234,
+        seed: 42,
+        replications: "10".to_string(),
+        parameters: vec![("classes".to_string(), "1:0.7,2:0.3".to_string())],
+    }
+}
+
+fn columns() -> Vec<Column> {
+    vec![
+        Column::new("n", ColumnType::Integer),
+        Column::new("loss", ColumnType::Float),
+    ]
+}
+
+fn write(path: &Path, format: Format) -> PathBuf {
+    let mut writer = ResultWriter::create(path, format, metadata(), columns()).unwrap();
+    writer
+        .write_row(vec![Value::from(10usize), 0.5.into()])
+        .unwrap();
+    writer
+        .write_row(vec![Value::from(20usize), 0.25.into()])
+        .unwrap();
+    writer.finish().unwrap()
+}
+
+#[test]
+fn every_experiment_gets_a_fresh_file() {
+    let dir = scratch_dir("fresh");
+    let path = dir.join("results.csv");
+    assert_eq!(write(&path, Format::Csv), path);
+    assert_eq!(write(&path, Format::Csv), dir.join("results-1.csv"));
+    assert_eq!(write(&path, Format::Csv), dir.join("results-2.csv"));
+
+    let text = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert!(lines.contains(&"# strategy=least-loaded"));
+    assert!(lines.contains(&"# topology_hash=0000000000001234"));
+    assert!(lines.contains(&"# seed=42"));
+    assert!(lines.contains(&"# classes=1:0.7,2:0.3"));
+    assert!(lines.contains(&"# types=integer,float"));
+    let data: Vec<&str> = lines.into_iter().filter(|l| !l.starts_with('#')).collect();
+    assert_eq!(data, ["n,loss", "10,0.5", "20,0.25"]);
+}
+
+#[test]
+fn json_lines_start_with_metadata() {
+    let dir = scratch_dir("jsonl");
+    let path = write(&dir.join("results.jsonl"), Format::JsonLines);
+    let text = fs::read_to_string(path).unwrap();
+    let lines: Vec<serde_json::Value> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(lines.len(), 3);
+    let metadata = &lines[0]["metadata"];
+    assert_eq!(metadata["seed"], 42);
+    assert_eq!(metadata["schema"][1]["type"], "float");
+    assert_eq!(lines[2], serde_json::json!({"n": 20, "loss": 0.25}));
+}
+
+#[test]
+fn columnar_holds_one_array_per_column() {
+    let dir = scratch_dir("columnar");
+    let path = write(&dir.join("results.json"), Format::Columnar);
+    let document: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(document["metadata"]["strategy"], "least-loaded");
+    assert_eq!(
+        document["columns"],
+        serde_json::json!([
+            {"name": "n", "values": [10, 20]},
+            {"name": "loss", "values": [0.5, 0.25]},
+        ])
+    );
+}
+
+#[test]
+fn columnar_is_written_when_the_writer_is_dropped() {
+    let dir = scratch_dir("columnar-drop");
+    let path = dir.join("results.json");
+    {
+        let mut writer =
+            ResultWriter::create(&path, Format::Columnar, metadata(), columns()).unwrap();
+        writer
+            .write_row(vec![Value::from(10usize), 0.5.into()])
+            .unwrap();
+        // 値はまだファイルに書かない
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+    }
+    let document: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(document["columns"][0]["values"], serde_json::json!([10]));
+}
+
+#[test]
+fn fingerprint_identifies_the_topology() {
+    let a = Topology::from_reader("0 1 1 3\n1 2 1 3\n".as_bytes()).unwrap();
+    let b = Topology::from_reader("# 同じ内容\n0 1 1 3\n\n1 2 1 3\n".as_bytes()).unwrap();
+    let c = Topology::from_reader("0 1 1 3\n1 2 1 4\n".as_bytes()).unwrap();
+    assert_eq!(a.fingerprint(), b.fingerprint());
+    assert_ne!(a.fingerprint(), c.fingerprint());
+}
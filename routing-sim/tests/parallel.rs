@@ -0,0 +1,105 @@
+use rayon::ThreadPoolBuilder;
+use routing_sim::process::ArrivalProcess;
+use routing_sim::rng;
+use routing_sim::router::{self, Router};
+use routing_sim::stats::StoppingRule;
+use routing_sim::{RunResult, Simulator, Topology};
+
+fn topology() -> Topology {
+    Topology::load(concat!(env!("CARGO_MANIFEST_DIR"), "/../distance.txt")).unwrap()
+}
+
+/// `threads`個のスレッドのプールで`f`を実行する
+fn with_threads<T: Send>(threads: usize, f: impl FnOnce() -> T + Send) -> T {
+    ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap()
+        .install(f)
+}
+
+/// 状態を持つ方式も含めて、並列に実行しても1本ずつ順に実行した結果と一致する
+#[test]
+fn replications_match_sequential_runs_for_any_thread_count() {
+    let t = topology();
+    let simulator = Simulator::new(&t, 2000).with_arrival(ArrivalProcess::Poisson { rate: 1.0 });
+    for name in [
+        "shortest-path",
+        "maximum-shortest-path-on-demand",
+        "dynamic-alternative",
+    ] {
+        let mut router = router::build(name, &t).unwrap();
+        let sequential: Vec<RunResult> = (0..12)
+            .map(|run| {
+                let mut rng = rng::stream(7, 20, run);
+                simulator.run(router.as_mut(), 20, &mut rng)
+            })
+            .collect();
+        for threads in [1, 3, 8] {
+            let results = with_threads(threads, || simulator.replicate(router.as_ref(), 20, 12, 7));
+            assert_eq!(results, sequential, "{} with {} threads", name, threads);
+        }
+    }
+}
+
+#[test]
+fn adaptive_replications_do_not_depend_on_thread_count() {
+    let t = topology();
+    let simulator = Simulator::new(&t, 1000);
+    let router = router::build("dynamic-alternative", &t).unwrap();
+    let rule = StoppingRule {
+        level: 0.95,
+        relative_half_width: 0.05,
+        min_runs: 3,
+        max_runs: 50,
+    };
+    let single = with_threads(1, || {
+        simulator.replicate_until(router.as_ref(), 25, 3, &rule)
+    });
+    let multi = with_threads(6, || {
+        simulator.replicate_until(router.as_ref(), 25, 3, &rule)
+    });
+    assert_eq!(single, multi);
+    assert_eq!(
+        single,
+        simulator.replicate(router.as_ref(), 25, single.len(), 3)
+    );
+}
+
+#[test]
+fn comparisons_do_not_depend_on_thread_count() {
+    let t = topology();
+    let simulator = Simulator::new(&t, 1000);
+    let routers: Vec<Box<dyn Router>> = ["least-loaded", "dynamic-alternative"]
+        .iter()
+        .map(|name| router::build(name, &t).unwrap())
+        .collect();
+    let single = with_threads(1, || simulator.compare(&routers, 30, 8, 11));
+    let multi = with_threads(5, || simulator.compare(&routers, 30, 8, 11));
+    assert_eq!(single, multi);
+}
+
+#[test]
+fn adaptive_comparisons_stop_when_every_strategy_is_precise() {
+    let t = topology();
+    let simulator = Simulator::new(&t, 1000);
+    let routers: Vec<Box<dyn Router>> = ["shortest-path", "k-shortest-path"]
+        .iter()
+        .map(|name| router::build(name, &t).unwrap())
+        .collect();
+    let rule = StoppingRule {
+        level: 0.95,
+        relative_half_width: 0.03,
+        min_runs: 3,
+        max_runs: 60,
+    };
+    let single = with_threads(1, || simulator.compare_until(&routers, 25, 5, &rule));
+    let multi = with_threads(4, || simulator.compare_until(&routers, 25, 5, &rule));
+    assert_eq!(single, multi);
+    assert!(single.len() >= 3 && single.len() < 60);
+    assert_eq!(single, simulator.compare(&routers, 25, single.len(), 5));
+    for i in 0..routers.len() {
+        let losses: Vec<f64> = single.iter().map(|run| run[i].call_loss_rate()).collect();
+        assert!(rule.is_satisfied(&losses));
+    }
+}
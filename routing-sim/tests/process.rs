@@ -0,0 +1,172 @@
+mod common;
+
+use std::collections::VecDeque;
+
+use common::call;
+use routing_sim::process::{ArrivalProcess, HoldingTime};
+use routing_sim::rng;
+use routing_sim::router;
+use routing_sim::{Call, Simulator, Topology};
+
+fn topology() -> Topology {
+    Topology::load(concat!(env!("CARGO_MANIFEST_DIR"), "/../distance.txt")).unwrap()
+}
+
+/// 各呼を`n`個後の呼が発生する直前に解放する従来のモデルで、確立できた呼の
+/// （始点, 終点, ホップ数）を到着順に返す
+fn legacy_routes(t: &Topology, calls: &[Call], n: usize) -> Vec<(usize, usize, usize)> {
+    let mut router = router::build("shortest-path", t).unwrap();
+    let mut bandwidth = t.capacities();
+    let mut history: VecDeque<Option<Vec<usize>>> = VecDeque::new();
+    let mut routes = Vec::new();
+    for call in calls {
+        if n > 0 && history.len() == n {
+            if let Some(Some(links)) = history.pop_front() {
+                for link in links {
+                    bandwidth[link] += 1;
+                }
+            }
+        }
+        let links = router
+            .find_path(call.source, call.destination, &bandwidth, 1)
+            .and_then(|path| t.path_links(&path))
+            .filter(|links| links.iter().all(|&link| bandwidth[link] >= 1));
+        if let Some(links) = &links {
+            for &link in links {
+                bandwidth[link] -= 1;
+            }
+            routes.push((call.source, call.destination, links.len()));
+        }
+        if n > 0 {
+            history.push_back(links);
+        } else if let Some(links) = links {
+            for link in links {
+                bandwidth[link] += 1;
+            }
+        }
+    }
+    routes
+}
+
+/// 既定の設定（等間隔到着・一定の保持時間）では従来のリングバッファのモデルと一致する
+#[test]
+fn default_engine_matches_the_legacy_ring_buffer() {
+    let t = topology();
+    let simulator = Simulator::new(&t, 3000);
+    for n in [0, 1, 7, 30] {
+        let calls = simulator.generate_calls(n, &mut rng::stream(1, n as u64, 0));
+        let mut router = router::build("shortest-path", &t).unwrap();
+        let result = simulator.run_calls(router.as_mut(), &calls, 0);
+        let routes: Vec<_> = result
+            .routes
+            .iter()
+            .map(|route| (route.source, route.destination, route.hops))
+            .collect();
+        let legacy = legacy_routes(&t, &calls, n);
+        assert_eq!(routes, legacy, "n = {}", n);
+        assert_eq!(result.total_success, legacy.len());
+    }
+}
+
+/// 終了と到着が同時刻なら、解放してから到着した呼を受け付ける
+#[test]
+fn releases_are_processed_before_arrivals_at_the_same_time() {
+    let t = Topology::from_reader("0 1 1 1\n".as_bytes()).unwrap();
+    let mut router = router::build("shortest-path", &t).unwrap();
+    // 1つ目の呼は時刻2に終了する。時刻1.5の呼は呼損、時刻2の呼は確立できる
+    let calls = [
+        call(0.0, 0, 1, 2.0),
+        call(1.5, 1, 0, 1.0),
+        call(2.0, 0, 1, 1.0),
+    ];
+    let result = Simulator::new(&t, 3).run_calls(router.as_mut(), &calls, 0);
+    assert_eq!(result.total_attempts, 3);
+    assert_eq!(result.total_success, 2);
+    assert_eq!(result.pairs[&(1, 0)].total_success, 0);
+    assert_eq!(result.pairs[&(0, 1)].total_success, 2);
+}
+
+#[test]
+fn deterministic_arrivals_are_evenly_spaced() {
+    let mut rng = rng::stream(0, 0, 0);
+    let mut arrivals = ArrivalProcess::Deterministic { rate: 4.0 }.arrivals();
+    let times: Vec<f64> = (0..5).map(|_| arrivals.next_time(&mut rng)).collect();
+    assert_eq!(times, [0.25, 0.5, 0.75, 1.0, 1.25]);
+}
+
+/// `count`個の到着の標本平均の到着率
+fn sample_rate(process: ArrivalProcess, count: usize) -> f64 {
+    let mut rng = rng::stream(3, 0, 0);
+    let mut arrivals = process.arrivals();
+    let mut time = 0.0;
+    for _ in 0..count {
+        let next = arrivals.next_time(&mut rng);
+        assert!(next >= time);
+        time = next;
+    }
+    count as f64 / time
+}
+
+#[test]
+fn poisson_arrivals_have_the_given_rate() {
+    let process = ArrivalProcess::Poisson { rate: 2.5 };
+    let rate = sample_rate(process, 100_000);
+    assert!((rate / process.mean_rate() - 1.0).abs() < 0.02, "{}", rate);
+}
+
+/// MMPPの標本平均の到着率は定常状態の到着率に近い
+#[test]
+fn mmpp_sample_rate_is_close_to_the_stationary_rate() {
+    let process = ArrivalProcess::Mmpp {
+        rates: [1.0, 5.0],
+        switch: [0.5, 0.25],
+    };
+    // 状態0に滞在する割合は1/3なので (1 + 2 * 5) / 3
+    assert!((process.mean_rate() - 11.0 / 3.0).abs() < 1e-12);
+    let rate = sample_rate(process, 200_000);
+    assert!((rate / process.mean_rate() - 1.0).abs() < 0.02, "{}", rate);
+}
+
+#[test]
+fn holding_times_have_the_given_mean() {
+    let mut rng = rng::stream(5, 0, 0);
+    assert_eq!(HoldingTime::Deterministic.sample(3.0, &mut rng), 3.0);
+    for holding in [
+        HoldingTime::Exponential,
+        HoldingTime::LogNormal { sigma: 0.5 },
+    ] {
+        let count = 100_000;
+        let samples: Vec<f64> = (0..count).map(|_| holding.sample(3.0, &mut rng)).collect();
+        assert!(samples.iter().all(|&sample| sample > 0.0));
+        let mean = samples.iter().sum::<f64>() / count as f64;
+        assert!((mean / 3.0 - 1.0).abs() < 0.02, "{}: {}", holding, mean);
+        assert_eq!(holding.sample(0.0, &mut rng), 0.0);
+    }
+}
+
+#[test]
+fn process_specs_are_parsed() {
+    assert_eq!(
+        "mmpp:1,5,0.5,0.25".parse::<ArrivalProcess>(),
+        Ok(ArrivalProcess::Mmpp {
+            rates: [1.0, 5.0],
+            switch: [0.5, 0.25]
+        })
+    );
+    assert_eq!(
+        "poisson"
+            .parse::<ArrivalProcess>()
+            .map(|p| p.with_rate(2.0)),
+        Ok(ArrivalProcess::Poisson { rate: 2.0 })
+    );
+    assert_eq!(
+        "lognormal:0.5".parse::<HoldingTime>(),
+        Ok(HoldingTime::LogNormal { sigma: 0.5 })
+    );
+    for spec in ["mmpp:1,5,0.5", "mmpp:1,5,0,1", "poisson:1", "uniform"] {
+        assert!(spec.parse::<ArrivalProcess>().is_err(), "{}", spec);
+    }
+    for spec in ["lognormal", "lognormal:-1", "exponential:2", "gamma"] {
+        assert!(spec.parse::<HoldingTime>().is_err(), "{}", spec);
+    }
+}
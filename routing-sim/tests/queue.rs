@@ -0,0 +1,74 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use routing_sim::router::{self, QueueKind, Router};
+use routing_sim::topology::{Link, Topology};
+
+fn random_topology(rng: &mut ChaCha8Rng, max_distance: isize) -> Topology {
+    let node_num = rng.gen_range(2..12);
+    let mut links = Vec::new();
+    for node1 in 0..node_num {
+        for node2 in node1 + 1..node_num {
+            if rng.gen_bool(0.4) {
+                links.push(Link {
+                    node1,
+                    node2,
+                    distance: rng.gen_range(0..=max_distance),
+                    capacity: rng.gen_range(1..4),
+                });
+            }
+        }
+    }
+    Topology::new(node_num, links)
+}
+
+fn on_demand(t: &Topology, queue: QueueKind) -> Box<dyn Router> {
+    let options = router::Options {
+        queue,
+        ..router::Options::default()
+    };
+    router::build_with("shortest-path-on-demand", t, &options).unwrap()
+}
+
+/// 全ノード対について、bucketと二分ヒープで同じ経路（したがって同じ距離）になる
+fn assert_same_paths(t: &Topology, bandwidth: &[isize]) {
+    let mut heap = on_demand(t, QueueKind::BinaryHeap);
+    let mut bucket = on_demand(t, QueueKind::Bucket);
+    for source in 0..t.node_num() {
+        for destination in 0..t.node_num() {
+            let expected = heap.find_path(source, destination, bandwidth, 1);
+            let found = bucket.find_path(source, destination, bandwidth, 1);
+            assert_eq!(
+                found.as_ref().map(|path| t.path_distance(path)),
+                expected.as_ref().map(|path| t.path_distance(path)),
+                "{} -> {}",
+                source,
+                destination
+            );
+            assert_eq!(found, expected, "{} -> {}", source, destination);
+        }
+    }
+}
+
+/// 距離が小さな整数のランダムなトポロジで、bucketは二分ヒープと同じ経路を返す
+#[test]
+fn bucket_queue_matches_the_heap_on_small_integer_distances() {
+    let mut rng = ChaCha8Rng::seed_from_u64(23);
+    for _ in 0..100 {
+        let t = random_topology(&mut rng, 4);
+        // 空き容量0のリンクを混ぜて、使えないリンクを避ける探索も比べる
+        let bandwidth: Vec<isize> = t.links().iter().map(|_| rng.gen_range(0..3)).collect();
+        assert_same_paths(&t, &bandwidth);
+    }
+}
+
+/// リンク数に比べて距離が大きければ、bucketを指定しても二分ヒープで探索する
+#[test]
+fn bucket_queue_falls_back_to_the_heap_for_large_distances() {
+    let mut rng = ChaCha8Rng::seed_from_u64(29);
+    for _ in 0..20 {
+        let t = random_topology(&mut rng, 1 << 40);
+        assert_same_paths(&t, &t.capacities());
+    }
+    let t = Topology::from_reader("0 1 9223372036854775807 1\n".as_bytes()).unwrap();
+    assert_same_paths(&t, &t.capacities());
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use common::call;
+use routing_sim::reservation::{LinkThreshold, TrunkReservation};
+use routing_sim::router;
+use routing_sim::{Call, Simulator, Topology};
+
+/// 容量3の三角形 0-1-2
+fn triangle() -> Topology {
+    Topology::from_reader("0 1 1 3\n1 2 1 3\n0 2 1 3\n".as_bytes()).unwrap()
+}
+
+#[test]
+fn min_hop_calls_may_use_reserved_capacity() {
+    let t = triangle();
+    let direct = t.link_between(0, 1).unwrap();
+    let alternate = [t.link_between(0, 2).unwrap(), t.link_between(2, 1).unwrap()];
+    let reservation = TrunkReservation::new(&t, 2);
+    assert_eq!(reservation.min_hops(0, 1), 1);
+
+    // 空き容量1は予約量2を下回るが、最小ホップ経路の呼は受け付ける
+    let mut bandwidth = vec![3; 3];
+    bandwidth[direct] = 1;
+    assert!(reservation.admits(0, 1, &[direct], &bandwidth, 1));
+    bandwidth[direct] = 0;
+    assert!(!reservation.admits(0, 1, &[direct], &bandwidth, 1));
+
+    // 迂回経路の呼は受け付けた後に予約量が残る場合だけ受け付ける
+    assert!(reservation.admits(0, 1, &alternate, &bandwidth, 1));
+    bandwidth[alternate[0]] = 2;
+    assert!(!reservation.admits(0, 1, &alternate, &bandwidth, 1));
+    bandwidth[alternate[0]] = 3;
+    assert!(!reservation.admits(0, 1, &alternate, &bandwidth, 2));
+}
+
+#[test]
+fn per_link_thresholds_override_the_global_one() {
+    let t = triangle();
+    let alternate = [t.link_between(0, 2).unwrap(), t.link_between(2, 1).unwrap()];
+    let bandwidth = vec![1; 3];
+
+    let mut reservation = TrunkReservation::new(&t, 1);
+    assert!(!reservation.admits(0, 1, &alternate, &bandwidth, 1));
+    for &link in &alternate {
+        reservation.set_threshold(link, 0);
+    }
+    assert_eq!(reservation.threshold(alternate[0]), 0);
+    assert!(reservation.admits(0, 1, &alternate, &bandwidth, 1));
+
+    // 予約しない設定でも、個別に予約したリンクの迂回呼は断る
+    let mut reservation = TrunkReservation::new(&t, 0);
+    assert!(reservation.admits(0, 1, &alternate, &bandwidth, 1));
+    reservation.set_threshold(alternate[1], 1);
+    assert_eq!(reservation.threshold(alternate[0]), 0);
+    assert!(!reservation.admits(0, 1, &alternate, &bandwidth, 1));
+}
+
+#[test]
+fn link_thresholds_are_parsed() {
+    assert_eq!(
+        "3 - 7 = 2".parse::<LinkThreshold>(),
+        Ok(LinkThreshold {
+            node1: 3,
+            node2: 7,
+            threshold: 2
+        })
+    );
+    assert_eq!(
+        "0-1=0"
+            .parse::<LinkThreshold>()
+            .map(|spec| spec.to_string()),
+        Ok("0-1=0".to_string())
+    );
+    for spec in [
+        "", "0-1", "0=1", "0-1=", "a-1=2", "0-b=2", "0-1=x", "0-1=1.5",
+    ] {
+        let error = spec.parse::<LinkThreshold>().unwrap_err();
+        assert!(
+            error.contains("expected node1-node2=threshold"),
+            "{}",
+            error
+        );
+    }
+    assert_eq!(
+        "0-1=-1".parse::<LinkThreshold>(),
+        Err("reservation must not be negative: -1".to_string())
+    );
+}
+
+/// 最初の候補が予約の条件を満たさなければ、条件を満たす別の候補を使う
+#[test]
+fn routers_fall_back_to_candidates_that_respect_the_reservation() {
+    // 0-1の直接のリンク（容量1）、2経由（距離2・容量2）、3経由（距離4・容量10）
+    let t = Topology::from_reader("0 1 1 1\n0 2 1 2\n2 1 1 2\n0 3 2 10\n3 1 2 10\n".as_bytes())
+        .unwrap();
+    let calls: Vec<Call> = (0..3).map(|i| call(i as f64, 0, 1, 100.0)).collect();
+    let simulator = Simulator::new(&t, calls.len()).with_reservation(TrunkReservation::new(&t, 2));
+    for name in [
+        "k-shortest-path",
+        "shortest-path-on-demand",
+        "maximum-shortest-path-on-demand",
+        "least-loaded",
+        "dynamic-alternative",
+    ] {
+        let mut router = router::build(name, &t).unwrap();
+        let result = simulator.run_calls(router.as_mut(), &calls, 0);
+        assert_eq!(result.total_success, 3, "{}", name);
+        // 2経由の経路は予約量を残せないので使わない
+        for route in &result.routes {
+            assert!(
+                route.hops == 1 || route.distance == 4,
+                "{}: {:?}",
+                name,
+                route
+            );
+        }
+    }
+
+    // 固定経路方式は最短経路だけを使う
+    let mut router = router::build("shortest-path", &t).unwrap();
+    assert_eq!(
+        simulator
+            .run_calls(router.as_mut(), &calls, 0)
+            .total_success,
+        1
+    );
+}
@@ -0,0 +1,116 @@
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use routing_sim::router::{self, WidthMode};
+use routing_sim::topology::{Link, Topology};
+
+const MODES: [WidthMode; 3] = [
+    WidthMode::WidestShortest,
+    WidthMode::ShortestWidest,
+    WidthMode::Widest,
+];
+
+fn random_topology(rng: &mut ChaCha8Rng) -> Topology {
+    let node_num = rng.gen_range(3..12);
+    let mut links = Vec::new();
+    for node1 in 0..node_num {
+        for node2 in node1 + 1..node_num {
+            if rng.gen_bool(0.4) {
+                links.push(Link {
+                    node1,
+                    node2,
+                    distance: rng.gen_range(0..4),
+                    capacity: rng.gen_range(1..8),
+                });
+            }
+        }
+    }
+    Topology::new(node_num, links)
+}
+
+/// 呼の受け付けと解放を繰り返しながら、キャッシュした経路と毎回探索した経路を比べる
+#[test]
+fn cached_routes_match_fresh_search_while_calls_come_and_go() {
+    let mut rng = ChaCha8Rng::seed_from_u64(19);
+    for _ in 0..60 {
+        let t = random_topology(&mut rng);
+        let node_num = t.node_num();
+        for width in MODES {
+            let options = router::Options {
+                width,
+                ..router::Options::default()
+            };
+            let mut router =
+                router::build_with("maximum-shortest-path-on-demand", &t, &options).unwrap();
+            let mut bandwidth = t.capacities();
+            let mut active: Vec<(Vec<usize>, isize)> = Vec::new();
+            for _ in 0..400 {
+                if !active.is_empty() && rng.gen_bool(0.4) {
+                    let i = rng.gen_range(0..active.len());
+                    let (links, demand) = active.swap_remove(i);
+                    for link in links {
+                        bandwidth[link] += demand;
+                    }
+                    continue;
+                }
+                let source = rng.gen_range(0..node_num);
+                let destination = rng.gen_range(0..node_num);
+                if source == destination {
+                    continue;
+                }
+                let demand = *[1, 1, 2, 3].choose(&mut rng).unwrap();
+                let path = router.find_path(source, destination, &bandwidth, demand);
+                assert_eq!(
+                    path,
+                    width.find_path(&t, &bandwidth, demand, source, destination)
+                );
+                if let Some(path) = path {
+                    let links = t.path_links(&path).unwrap();
+                    for &link in &links {
+                        bandwidth[link] -= demand;
+                    }
+                    active.push((links, demand));
+                }
+            }
+        }
+    }
+}
+
+/// 空き容量を任意に変えても結果が変わらない
+#[test]
+fn cached_routes_match_fresh_search_under_arbitrary_changes() {
+    let mut rng = ChaCha8Rng::seed_from_u64(23);
+    for _ in 0..60 {
+        let t = random_topology(&mut rng);
+        let node_num = t.node_num();
+        let link_num = t.links().len();
+        if link_num == 0 {
+            continue;
+        }
+        for width in MODES {
+            let options = router::Options {
+                width,
+                ..router::Options::default()
+            };
+            let mut router =
+                router::build_with("maximum-shortest-path-on-demand", &t, &options).unwrap();
+            let mut bandwidth = t.capacities();
+            for _ in 0..300 {
+                for _ in 0..rng.gen_range(0..3) {
+                    let link = rng.gen_range(0..link_num);
+                    bandwidth[link] = rng.gen_range(0..8);
+                }
+                let source = rng.gen_range(0..node_num);
+                let destination = rng.gen_range(0..node_num);
+                if source == destination {
+                    continue;
+                }
+                let demand = rng.gen_range(1..3);
+                assert_eq!(
+                    router.find_path(source, destination, &bandwidth, demand),
+                    width.find_path(&t, &bandwidth, demand, source, destination)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,91 @@
+mod common;
+
+use common::call;
+use routing_sim::router;
+use routing_sim::routes::RouteStats;
+use routing_sim::{RouteResult, RunResult, Simulator, Topology};
+
+/// 0-1-2の距離1のリンクと、0-2の距離3のリンク
+fn triangle() -> Topology {
+    Topology::from_reader("0 1 1 1\n1 2 1 1\n0 2 3 1\n".as_bytes()).unwrap()
+}
+
+fn route(source: usize, destination: usize, hops: usize, distance: isize) -> RouteResult {
+    RouteResult {
+        source,
+        destination,
+        hops,
+        distance,
+    }
+}
+
+#[test]
+fn histograms_pool_routes_over_runs() {
+    let run = |routes: Vec<RouteResult>| RunResult {
+        routes,
+        ..RunResult::default()
+    };
+    let results = [
+        run(vec![route(0, 2, 2, 2), route(0, 1, 1, 1)]),
+        run(vec![route(2, 0, 1, 3), route(1, 0, 2, 4)]),
+    ];
+    let stats = RouteStats::new(&triangle(), &results);
+
+    assert_eq!(stats.calls(), 4);
+    assert_eq!(stats.share(2), 0.5);
+    assert_eq!(stats.hop_histogram(), [(1, 2), (2, 2)]);
+    assert_eq!(stats.distance_histogram(), [(1, 1), (2, 1), (3, 1), (4, 1)]);
+    // 伸長率は1, 1, 1.5, 4
+    assert_eq!(stats.stretch_histogram(), [(1.0, 2), (1.5, 1), (4.0, 1)]);
+    assert_eq!(stats.mean_stretch(), Some(7.5 / 4.0));
+    assert_eq!(stats.max_stretch(), Some(4.0));
+    assert_eq!(stats.mean_hops(), Some(1.5));
+    assert_eq!(stats.mean_distance(), Some(2.5));
+    // 0→2と1→0の2ホップの経路は直接のリンクより1ホップ多い
+    assert_eq!(stats.mean_extra_hops(), Some(0.5));
+}
+
+#[test]
+fn simulator_records_routes_of_accepted_calls_only() {
+    let t = triangle();
+    // 2つ目の呼は最短経路の容量が無く呼損になる
+    let calls = [
+        call(0.0, 0, 2, 10.0),
+        call(1.0, 0, 2, 10.0),
+        call(20.0, 0, 2, 10.0),
+    ];
+    let mut router = router::build("shortest-path", &t).unwrap();
+    let result = Simulator::new(&t, calls.len()).run_calls(router.as_mut(), &calls, 0);
+    assert_eq!(result.total_success, 2);
+    assert_eq!(result.routes, [route(0, 2, 2, 2), route(0, 2, 2, 2)]);
+
+    let stats = RouteStats::new(&t, &[result]);
+    assert_eq!(stats.stretch_histogram(), [(1.0, 2)]);
+    assert_eq!(stats.mean_extra_hops(), Some(1.0));
+}
+
+/// 確立できた呼が無ければ平均は定まらず、割合は0
+#[test]
+fn stats_without_accepted_calls_have_no_means() {
+    let stats = RouteStats::new(&triangle(), &[RunResult::default()]);
+    assert_eq!(stats.calls(), 0);
+    assert_eq!(stats.mean_hops(), None);
+    assert_eq!(stats.mean_extra_hops(), None);
+    assert_eq!(stats.mean_distance(), None);
+    assert_eq!(stats.mean_stretch(), None);
+    assert_eq!(stats.max_stretch(), None);
+    assert_eq!(stats.share(0), 0.0);
+    assert!(stats.hop_histogram().is_empty());
+    assert!(stats.stretch_histogram().is_empty());
+
+    // 最短距離が0のノード対の呼だけなら伸長率は定まらない
+    let t = Topology::from_reader("0 1 0 1\n".as_bytes()).unwrap();
+    let result = RunResult {
+        routes: vec![route(0, 1, 1, 0)],
+        ..RunResult::default()
+    };
+    let stats = RouteStats::new(&t, &[result]);
+    assert_eq!(stats.mean_hops(), Some(1.0));
+    assert_eq!(stats.mean_stretch(), None);
+    assert_eq!(stats.max_stretch(), None);
+}
@@ -0,0 +1,127 @@
+use routing_sim::stats::{
+    mser, student_t_cdf, student_t_quantile, ConfidenceInterval, PairedDifference, StoppingRule,
+};
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "{} is not within {} of {}",
+        actual,
+        tolerance,
+        expected
+    );
+}
+
+#[test]
+fn student_t_quantiles_match_the_table() {
+    // (p, 自由度, 表の値)
+    let table = [
+        (0.975, 1.0, 12.706),
+        (0.975, 2.0, 4.303),
+        (0.975, 9.0, 2.262),
+        (0.975, 30.0, 2.042),
+        (0.95, 9.0, 1.833),
+        (0.995, 4.0, 4.604),
+    ];
+    for (p, df, expected) in table {
+        assert_close(student_t_quantile(p, df), expected, 5e-4);
+        assert_close(student_t_cdf(expected, df), p, 1e-4);
+    }
+    assert_eq!(student_t_quantile(0.5, 3.0), 0.0);
+    assert_close(student_t_quantile(0.025, 9.0), -2.262, 5e-4);
+}
+
+#[test]
+fn confidence_interval_uses_the_t_quantile() {
+    // 平均3、標本標準偏差√2.5、自由度4
+    let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+    let interval = ConfidenceInterval::new(&values, 0.95);
+    assert_eq!(interval.mean, 3.0);
+    assert_close(interval.std_dev, 2.5f64.sqrt(), 1e-12);
+    let half_width = 2.776 * 2.5f64.sqrt() / 5f64.sqrt();
+    assert_close(interval.half_width, half_width, 1e-3);
+    assert_close(interval.lower(), 3.0 - half_width, 1e-3);
+    assert_close(interval.upper(), 3.0 + half_width, 1e-3);
+    assert_close(interval.relative_half_width(), half_width / 3.0, 1e-3);
+
+    assert_eq!(
+        ConfidenceInterval::new(&[0.4], 0.95).half_width,
+        f64::INFINITY
+    );
+    assert_eq!(
+        ConfidenceInterval::new(&[0.0, 0.0], 0.95).relative_half_width(),
+        0.0
+    );
+}
+
+#[test]
+fn stopping_rule_ends_early_on_low_variance() {
+    let rule = StoppingRule {
+        level: 0.95,
+        relative_half_width: 0.01,
+        min_runs: 3,
+        max_runs: 100,
+    };
+    // ばらつきの小さい値は最少回数で止まる
+    let steady = [0.300, 0.301, 0.299, 0.300];
+    let stop = (0..=steady.len())
+        .find(|&count| rule.is_satisfied(&steady[..count]))
+        .unwrap();
+    assert_eq!(stop, 3);
+
+    // ばらつきの大きい値は最多回数まで止まらない
+    let noisy: Vec<f64> = (0..100).map(|i| [0.1, 0.5][i % 2]).collect();
+    assert!(!rule.is_satisfied(&noisy[..99]));
+    assert!(rule.is_satisfied(&noisy));
+    // 最少回数は2回未満にならない
+    let single = StoppingRule {
+        min_runs: 1,
+        ..rule
+    };
+    assert!(!single.is_satisfied(&[0.3]));
+}
+
+/// 先頭の過渡期の後に値が一定になる列では、過渡期のバッチだけを捨てる
+#[test]
+fn mser_detects_a_leading_step() {
+    let mut values = vec![1.0; 20];
+    values.extend([0.0; 80]);
+    assert_eq!(mser(&values, 5), 20);
+    // 過渡期がバッチの途中で終わる場合は、そのバッチまで捨てる
+    let mut values = vec![1.0; 22];
+    values.extend([0.0; 78]);
+    assert_eq!(mser(&values, 5), 25);
+    // 定常な列からは何も捨てない
+    assert_eq!(mser(&[0.5; 100], 5), 0);
+}
+
+/// 単調に増える列ではMSERは捨てるほど小さくなるが、バッチ数の半分で止める
+#[test]
+fn mser_truncation_is_capped_at_half_the_batches() {
+    let values: Vec<f64> = (0..100).map(f64::from).collect();
+    assert_eq!(mser(&values, 5), 50);
+    // 端数のバッチは数えない（21バッチの半分は10バッチ）
+    let values: Vec<f64> = (0..108).map(f64::from).collect();
+    assert_eq!(mser(&values, 5), 50);
+    // バッチが2つ未満なら捨てない
+    assert_eq!(mser(&values[..9], 5), 0);
+}
+
+#[test]
+fn paired_difference_matches_a_hand_calculation() {
+    // 差は0.2, 0.3, 0.0で、平均1/6、偏差の二乗和は0.14/3
+    let difference = PairedDifference::new([(0.3, 0.1), (0.5, 0.2), (0.4, 0.4)]);
+    assert_close(difference.mean, 1.0 / 6.0, 1e-12);
+    assert_close(difference.variance, 0.07 / 3.0, 1e-12);
+    assert_close(difference.std_error, (0.07 / 9.0_f64).sqrt(), 1e-12);
+    // 符号は1つ目から2つ目を引いた向き
+    let reversed = PairedDifference::new([(0.1, 0.3), (0.2, 0.5), (0.4, 0.4)]);
+    assert_close(reversed.mean, -1.0 / 6.0, 1e-12);
+    assert_close(reversed.variance, difference.variance, 1e-12);
+    // 組が1つなら分散は0
+    let single = PairedDifference::new([(0.5, 0.25)]);
+    assert_eq!(
+        (single.mean, single.variance, single.std_error),
+        (0.25, 0.0, 0.0)
+    );
+}
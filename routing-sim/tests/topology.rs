@@ -0,0 +1,201 @@
+use routing_sim::topology::{Adjacent, ParseErrorKind};
+use routing_sim::{Topology, TopologyError};
+
+fn parse(text: &str) -> Result<Topology, TopologyError> {
+    Topology::from_reader(text.as_bytes())
+}
+
+/// 解析エラーの（行, 桁, 種類）
+fn parse_error(text: &str) -> (usize, usize, ParseErrorKind) {
+    match parse(text) {
+        Err(TopologyError::Parse { line, column, kind }) => (line, column, kind),
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn comments_blank_lines_and_spacing_are_ignored() {
+    let t = parse("# 三角形\n\n0 1 2 5  # 末尾のコメント\n  1\t2 3 6\n0 2 4 7\n").unwrap();
+    assert_eq!(t.node_num(), 3);
+    assert_eq!(t.links().len(), 3);
+    assert_eq!(t.links()[1].distance, 3);
+    assert_eq!(t.links()[1].capacity, 6);
+    assert_eq!(
+        t.neighbors(0),
+        [Adjacent { node: 1, link: 0 }, Adjacent { node: 2, link: 2 }]
+    );
+    assert_eq!(t.link_between(2, 1), Some(1));
+}
+
+#[test]
+fn declared_node_count_allows_isolated_nodes() {
+    let t = parse("nodes 5\n0 1 1 1\n").unwrap();
+    assert_eq!(t.node_num(), 5);
+    assert!(t.neighbors(4).is_empty());
+}
+
+#[test]
+fn errors_point_at_the_offending_field() {
+    let cases = [
+        (
+            "0 1 1\n",
+            (
+                1,
+                1,
+                ParseErrorKind::FieldCount {
+                    expected: 4,
+                    found: 3,
+                },
+            ),
+        ),
+        (
+            "0 1 1 1\n1 x 1 1\n",
+            (
+                2,
+                3,
+                ParseErrorKind::InvalidNumber {
+                    value: "x".to_string(),
+                },
+            ),
+        ),
+        (
+            "0 1 1 1\n1 -2 1 1\n",
+            (
+                2,
+                3,
+                ParseErrorKind::InvalidNumber {
+                    value: "-2".to_string(),
+                },
+            ),
+        ),
+        (
+            "nodes 3\n0 1 1 1\n1  3 1 1\n",
+            (
+                3,
+                4,
+                ParseErrorKind::NodeOutOfRange {
+                    node: 3,
+                    node_num: 3,
+                },
+            ),
+        ),
+        (
+            "0 1 1 1\n2 2 1 1\n",
+            (2, 3, ParseErrorKind::SelfLoop { node: 2 }),
+        ),
+        (
+            "0 1 1 1\n1 2 1 1\n1 0 5 5\n",
+            (
+                3,
+                1,
+                ParseErrorKind::DuplicateLink {
+                    node1: 1,
+                    node2: 0,
+                    first_line: 1,
+                },
+            ),
+        ),
+        (
+            "0 1 -1 1\n",
+            (1, 5, ParseErrorKind::NegativeDistance { distance: -1 }),
+        ),
+        (
+            "0 1 1 0\n",
+            (1, 7, ParseErrorKind::NonPositiveCapacity { capacity: 0 }),
+        ),
+        (
+            "nodes 3\nnodes 4\n",
+            (2, 1, ParseErrorKind::DuplicateNodeCount),
+        ),
+        (
+            "0 1 1 1\nnodes 4\n",
+            (2, 1, ParseErrorKind::NodeCountAfterLinks),
+        ),
+        (
+            "nodes 3 4\n",
+            (
+                1,
+                1,
+                ParseErrorKind::FieldCount {
+                    expected: 2,
+                    found: 3,
+                },
+            ),
+        ),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(parse_error(text), expected, "{:?}", text);
+    }
+}
+
+/// `nodes`が無ければノード番号は0から隙間なく使う
+#[test]
+fn node_ids_must_be_contiguous_without_a_node_count() {
+    let cases = [
+        (
+            "0 1 1 1\n1 18446744073709551615 1 1\n",
+            (
+                2,
+                3,
+                ParseErrorKind::NodeOutOfRange {
+                    node: usize::MAX,
+                    node_num: 3,
+                },
+            ),
+        ),
+        (
+            "1 200000 1 1\n",
+            (
+                1,
+                3,
+                ParseErrorKind::NodeOutOfRange {
+                    node: 200000,
+                    node_num: 2,
+                },
+            ),
+        ),
+        (
+            "0 1 1 1\n3 1 1 1\n",
+            (
+                2,
+                1,
+                ParseErrorKind::NodeOutOfRange {
+                    node: 3,
+                    node_num: 3,
+                },
+            ),
+        ),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(parse_error(text), expected, "{:?}", text);
+    }
+    // `nodes`で宣言すれば番号に隙間があってもよい
+    assert_eq!(parse("nodes 4\n0 1 1 1\n3 1 1 1\n").unwrap().node_num(), 4);
+    assert_eq!(parse("1 0 1 1\n2 1 1 1\n").unwrap().node_num(), 3);
+}
+
+#[test]
+fn topologies_need_at_least_two_nodes() {
+    assert_eq!(
+        parse_error(""),
+        (1, 1, ParseErrorKind::TooFewNodes { node_num: 0 })
+    );
+    assert_eq!(
+        parse_error("# 空\n\n"),
+        (3, 1, ParseErrorKind::TooFewNodes { node_num: 0 })
+    );
+    assert_eq!(
+        parse_error("# 1ノード\nnodes 1\n"),
+        (2, 7, ParseErrorKind::TooFewNodes { node_num: 1 })
+    );
+    assert_eq!(parse("nodes 2\n").unwrap().node_num(), 2);
+}
+
+#[test]
+fn errors_display_line_and_column() {
+    let error = parse("0 1 1 1\n0 1 1 1\n").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "line 2, column 1: link 0-1 is already defined on line 1"
+    );
+}
@@ -0,0 +1,206 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use routing_sim::process::ArrivalProcess;
+use routing_sim::topology::ParseErrorKind;
+use routing_sim::traffic::{TrafficError, TrafficModel};
+use routing_sim::{Simulator, Topology, TrafficMatrix};
+
+/// 解析エラーの（行, 桁, 種類）
+fn parse_error(text: &str, node_num: usize) -> (usize, usize, ParseErrorKind) {
+    match TrafficMatrix::from_reader(text.as_bytes(), node_num) {
+        Err(TrafficError::Parse { line, column, kind }) => (line, column, kind),
+        other => panic!("expected a parse error, got {:?}", other),
+    }
+}
+
+#[test]
+fn matrix_file_gives_per_pair_loads() {
+    let text = "# 始点 終点 呼量\n0 1 3\n\n2 0 1.5  # コメント\n";
+    let matrix = TrafficMatrix::from_reader(text.as_bytes(), 3).unwrap();
+    assert_eq!(matrix.weight(0, 1), 3.0);
+    assert_eq!(matrix.weight(2, 0), 1.5);
+    assert_eq!(matrix.weight(1, 0), 0.0);
+    assert_eq!(matrix.total_weight(), 4.5);
+    assert_eq!(matrix.offered_load(), Some(4.5));
+
+    // 組み込みの行列の値は比なので呼量を持たない
+    assert_eq!(TrafficMatrix::uniform(3).offered_load(), None);
+    assert_eq!(TrafficMatrix::hot_spot(3, 0, 2.0).offered_load(), None);
+}
+
+/// 行列ファイルの呼量の合計を保持時間で割った到着率で呼が発生する
+#[test]
+fn matrix_file_loads_set_the_arrival_rate() {
+    let t = Topology::from_reader("0 1 1 1\n1 2 1 1\n".as_bytes()).unwrap();
+    let matrix = TrafficMatrix::from_reader("0 1 3\n2 0 1.5\n".as_bytes(), 3).unwrap();
+    let simulator = Simulator::new(&t, 20_000)
+        .with_arrival(ArrivalProcess::Poisson { rate: 7.0 })
+        .with_traffic(matrix);
+    let n = 3;
+    assert_eq!(simulator.arrival(n), ArrivalProcess::Poisson { rate: 1.5 });
+    let calls = simulator.generate_calls(n, &mut ChaCha8Rng::seed_from_u64(2));
+    let rate = calls.len() as f64 / calls.last().unwrap().time;
+    assert!((rate / 1.5 - 1.0).abs() < 0.03, "{}", rate);
+    // ノード対の到着率は呼量 / n
+    let first = calls.iter().filter(|call| call.source == 0).count();
+    assert!((first as f64 / calls.len() as f64 - 3.0 / 4.5).abs() < 0.02);
+
+    // 組み込みの行列では全体の到着率をそのまま使う
+    let builtin = Simulator::new(&t, 10).with_arrival(ArrivalProcess::Poisson { rate: 7.0 });
+    assert_eq!(builtin.arrival(n), ArrivalProcess::Poisson { rate: 7.0 });
+}
+
+#[test]
+fn matrix_file_errors_point_at_the_offending_field() {
+    let cases = [
+        (
+            "0 1\n",
+            (
+                1,
+                1,
+                ParseErrorKind::FieldCount {
+                    expected: 3,
+                    found: 2,
+                },
+            ),
+        ),
+        (
+            "0 1 1\n0 x 1\n",
+            (
+                2,
+                3,
+                ParseErrorKind::InvalidNumber {
+                    value: "x".to_string(),
+                },
+            ),
+        ),
+        (
+            "0 3 1\n",
+            (
+                1,
+                3,
+                ParseErrorKind::NodeOutOfRange {
+                    node: 3,
+                    node_num: 3,
+                },
+            ),
+        ),
+        ("1 1 1\n", (1, 3, ParseErrorKind::SelfLoop { node: 1 })),
+        (
+            "0 1 -2\n",
+            (
+                1,
+                5,
+                ParseErrorKind::InvalidLoad {
+                    value: "-2".to_string(),
+                },
+            ),
+        ),
+        (
+            "0 1 inf\n",
+            (
+                1,
+                5,
+                ParseErrorKind::InvalidLoad {
+                    value: "inf".to_string(),
+                },
+            ),
+        ),
+        (
+            "0 1 1\n1 0 1\n0  1 2\n",
+            (
+                3,
+                1,
+                ParseErrorKind::DuplicatePair {
+                    source: 0,
+                    destination: 1,
+                    first_line: 1,
+                },
+            ),
+        ),
+    ];
+    for (text, expected) in cases {
+        assert_eq!(parse_error(text, 3), expected, "{:?}", text);
+    }
+    assert!(matches!(
+        TrafficMatrix::from_reader("0 1 0\n".as_bytes(), 3),
+        Err(TrafficError::NoTraffic)
+    ));
+}
+
+#[test]
+fn gravity_weights_are_products_of_attached_capacity() {
+    // ノード0: 2, ノード1: 2 + 3, ノード2: 3
+    let t = Topology::from_reader("0 1 1 2\n1 2 1 3\n".as_bytes()).unwrap();
+    let matrix = TrafficModel::Gravity.build(&t);
+    assert_eq!(matrix.weight(0, 1), 10.0);
+    assert_eq!(matrix.weight(1, 2), 15.0);
+    assert_eq!(matrix.weight(2, 0), 6.0);
+    assert_eq!(matrix.weight(1, 1), 0.0);
+    assert_eq!(matrix.total_weight(), 2.0 * (10.0 + 15.0 + 6.0));
+}
+
+#[test]
+fn hot_spot_scales_pairs_touching_the_node() {
+    let model: TrafficModel = "hotspot:2,4".parse().unwrap();
+    assert_eq!(
+        model,
+        TrafficModel::HotSpot {
+            node: 2,
+            factor: 4.0
+        }
+    );
+    let matrix = TrafficMatrix::hot_spot(4, 2, 4.0);
+    assert_eq!(matrix.weight(2, 0), 4.0);
+    assert_eq!(matrix.weight(3, 2), 4.0);
+    assert_eq!(matrix.weight(0, 1), 1.0);
+    assert_eq!(matrix.weight(2, 2), 0.0);
+
+    for spec in [
+        "hotspot:2",
+        "hotspot:x,4",
+        "hotspot:2,0",
+        "hotspot:2,-1",
+        "gravity:1",
+    ] {
+        assert!(spec.parse::<TrafficModel>().is_err(), "{}", spec);
+    }
+}
+
+#[test]
+fn pairs_are_drawn_in_proportion_to_weights() {
+    let matrix = TrafficMatrix::from_reader("0 1 3\n2 1 1\n".as_bytes(), 3).unwrap();
+    let sampler = matrix.sampler().unwrap();
+    let mut rng = ChaCha8Rng::seed_from_u64(5);
+    let draws = 40_000;
+    let mut first = 0;
+    for _ in 0..draws {
+        match sampler.sample(&mut rng) {
+            (0, 1) => first += 1,
+            (2, 1) => {}
+            pair => panic!("pair {:?} has no weight", pair),
+        }
+    }
+    assert!((first as f64 / draws as f64 - 0.75).abs() < 0.01);
+}
+
+#[test]
+fn matrices_without_pairs_have_no_sampler() {
+    assert!(TrafficMatrix::uniform(0).sampler().is_none());
+    assert!(TrafficMatrix::uniform(1).sampler().is_none());
+    assert!(TrafficMatrix::from_fn(3, |_, _| 0.0).sampler().is_none());
+
+    let sampler = TrafficMatrix::uniform(2).sampler().unwrap();
+    let mut rng = ChaCha8Rng::seed_from_u64(1);
+    for _ in 0..100 {
+        let (source, destination) = sampler.sample(&mut rng);
+        assert_ne!(source, destination);
+        assert!(source < 2 && destination < 2);
+    }
+
+    // 呼を発生させるノード対が無ければ呼の列は空になる
+    let single = Topology::new(1, Vec::new());
+    assert!(Simulator::new(&single, 10)
+        .generate_calls(1, &mut rng)
+        .is_empty());
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use common::call;
+use routing_sim::router;
+use routing_sim::{Simulator, Warmup};
+
+#[test]
+fn mser5_truncates_the_leading_blocked_calls() {
+    let mut blocked = vec![true; 20];
+    blocked.extend([false; 80]);
+    assert_eq!(Warmup::Mser5.truncation(&blocked), 20);
+    assert_eq!(Warmup::None.truncation(&blocked), 0);
+    assert_eq!(Warmup::Calls(30).truncation(&blocked), 30);
+    assert_eq!(Warmup::Calls(500).truncation(&blocked), 100);
+}
+
+/// ウォームアップの呼は呼の数にもクラス・ノード対ごとの数にも含めない
+#[test]
+fn warm_up_calls_are_left_out_of_the_blocking_counts() {
+    let t = common::line();
+    // 最初の呼が時刻3まで両方のリンクを塞ぎ、続く2つの呼は呼損になる
+    let calls = [
+        call(0.0, 0, 2, 3.0),
+        call(1.0, 0, 1, 1.0),
+        call(2.0, 1, 2, 1.0),
+        call(3.0, 0, 1, 1.0),
+        call(4.0, 1, 2, 1.0),
+        call(5.0, 0, 2, 1.0),
+    ];
+    let run = |warmup| {
+        let mut router = router::build("shortest-path", &t).unwrap();
+        Simulator::new(&t, calls.len())
+            .with_warmup(warmup)
+            .run_calls(router.as_mut(), &calls, 0)
+    };
+
+    let all = run(Warmup::None);
+    assert_eq!((all.total_attempts, all.total_success), (6, 4));
+
+    let result = run(Warmup::Calls(2));
+    assert_eq!(result.warmup, 2);
+    assert_eq!((result.total_attempts, result.total_success), (4, 3));
+    assert_eq!(result.call_loss_rate(), 0.25);
+    assert_eq!(result.classes[0].total_attempts, 4);
+    assert_eq!(result.classes[0].total_success, 3);
+    assert_eq!(result.pairs[&(0, 2)].total_attempts, 1);
+    assert_eq!(result.pairs[&(1, 2)].total_attempts, 2);
+    assert_eq!(result.pairs[&(1, 2)].total_success, 1);
+    assert_eq!(result.pairs[&(0, 1)].total_attempts, 1);
+    assert_eq!(result.pairs[&(0, 1)].total_success, 1);
+
+    // 呼の数より長いウォームアップでは何も集計しない
+    let result = run(Warmup::Calls(10));
+    assert_eq!(result.warmup, 6);
+    assert_eq!(result.total_attempts, 0);
+    assert!(result.pairs.is_empty());
+    // 集計する呼が無ければ呼損も無いので呼損率は0（NaNにしない）
+    assert_eq!(result.call_loss_rate(), 0.0);
+    assert_eq!(result.classes[0].call_loss_rate(), 0.0);
+}
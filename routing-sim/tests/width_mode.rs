@@ -0,0 +1,228 @@
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use routing_sim::router::{self, path_width, WidthMode};
+use routing_sim::topology::{Link, Topology};
+
+/// `接続元 接続先 距離 容量` の行からトポロジを作る
+fn topology(text: &str) -> Topology {
+    Topology::from_reader(text.as_bytes()).unwrap()
+}
+
+/// 0→3の経路が4本ある：
+/// 0-1-3（距離2・幅1）、0-5-3（距離2・幅2）、0-2-3（距離4・幅5）、0-4-3（距離6・幅3）
+fn four_routes() -> Topology {
+    topology(
+        "0 1 1 1\n1 3 1 1\n\
+         0 5 1 2\n5 3 1 2\n\
+         0 2 2 5\n2 3 2 5\n\
+         0 4 1 3\n4 3 5 3\n",
+    )
+}
+
+#[test]
+fn widest_shortest_breaks_distance_ties_by_width() {
+    let t = four_routes();
+    let path = WidthMode::WidestShortest.find_path(&t, &t.capacities(), 1, 0, 3);
+    assert_eq!(path, Some(vec![0, 5, 3]));
+}
+
+#[test]
+fn shortest_widest_prefers_width_over_distance() {
+    let t = four_routes();
+    let path = WidthMode::ShortestWidest.find_path(&t, &t.capacities(), 1, 0, 3);
+    assert_eq!(path, Some(vec![0, 2, 3]));
+}
+
+#[test]
+fn demand_excludes_narrow_links() {
+    let t = four_routes();
+    let capacity = t.capacities();
+    assert_eq!(
+        WidthMode::WidestShortest.find_path(&t, &capacity, 3, 0, 3),
+        Some(vec![0, 2, 3])
+    );
+    for mode in [
+        WidthMode::WidestShortest,
+        WidthMode::ShortestWidest,
+        WidthMode::Widest,
+    ] {
+        assert_eq!(mode.find_path(&t, &capacity, 6, 0, 3), None);
+    }
+}
+
+#[test]
+fn widest_ignores_distance_and_follows_the_tie_rule() {
+    // 0-1-3（距離10・幅4）と0-2-3（距離2・幅4）
+    let t = topology("0 1 5 4\n1 3 5 4\n0 2 1 4\n2 3 1 4\n");
+    let capacity = t.capacities();
+    // 幅が同じならノード番号の小さいノード1から確定し、後から同じ幅の経路が見つかっても更新しない
+    assert_eq!(
+        WidthMode::Widest.find_path(&t, &capacity, 1, 0, 3),
+        Some(vec![0, 1, 3])
+    );
+    assert_eq!(
+        WidthMode::ShortestWidest.find_path(&t, &capacity, 1, 0, 3),
+        Some(vec![0, 2, 3])
+    );
+}
+
+#[test]
+fn fixed_router_uses_the_selected_mode() {
+    let t = four_routes();
+    let options = router::Options {
+        width: WidthMode::WidestShortest,
+        ..router::Options::default()
+    };
+    let mut router = router::build_with("maximum-shortest-path", &t, &options).unwrap();
+    assert_eq!(
+        router.find_path(0, 3, &t.capacities(), 1),
+        Some(vec![0, 5, 3])
+    );
+    let mut router = router::build("maximum-shortest-path", &t).unwrap();
+    assert_eq!(
+        router.find_path(0, 3, &t.capacities(), 1),
+        Some(vec![0, 2, 3])
+    );
+}
+
+/// `source`から`destination`へのループの無い経路をすべて列挙する
+fn simple_paths(
+    t: &Topology,
+    capacity: &[isize],
+    demand: isize,
+    source: usize,
+    destination: usize,
+) -> Vec<Vec<usize>> {
+    fn visit(
+        t: &Topology,
+        capacity: &[isize],
+        demand: isize,
+        destination: usize,
+        path: &mut Vec<usize>,
+        paths: &mut Vec<Vec<usize>>,
+    ) {
+        let node = *path.last().unwrap();
+        if node == destination {
+            paths.push(path.clone());
+            return;
+        }
+        for adjacent in t.neighbors(node) {
+            if capacity[adjacent.link] >= demand && !path.contains(&adjacent.node) {
+                path.push(adjacent.node);
+                visit(t, capacity, demand, destination, path, paths);
+                path.pop();
+            }
+        }
+    }
+    let mut paths = Vec::new();
+    visit(
+        t,
+        capacity,
+        demand,
+        destination,
+        &mut vec![source],
+        &mut paths,
+    );
+    paths
+}
+
+#[test]
+fn modes_match_exhaustive_search() {
+    let mut rng = ChaCha8Rng::seed_from_u64(17);
+    for _ in 0..200 {
+        let node_num = rng.gen_range(3..8);
+        let mut links = Vec::new();
+        for node1 in 0..node_num {
+            for node2 in node1 + 1..node_num {
+                if rng.gen_bool(0.5) {
+                    links.push(Link {
+                        node1,
+                        node2,
+                        distance: rng.gen_range(0..4),
+                        capacity: rng.gen_range(1..6),
+                    });
+                }
+            }
+        }
+        let t = Topology::new(node_num, links);
+        let capacity: Vec<isize> = t.links().iter().map(|_| rng.gen_range(0..6)).collect();
+        let demand = rng.gen_range(1..3);
+
+        for source in 0..node_num {
+            for destination in 0..node_num {
+                if source == destination {
+                    continue;
+                }
+                // 経路の評価（距離, 幅）
+                let score =
+                    |path: &[usize]| (t.path_distance(path), path_width(&t, &capacity, path));
+                let scores: Vec<(isize, isize)> =
+                    simple_paths(&t, &capacity, demand, source, destination)
+                        .iter()
+                        .map(|path| score(path))
+                        .collect();
+                let widest_shortest = scores.iter().map(|&(d, w)| (d, -w)).min();
+                let shortest_widest = scores.iter().map(|&(d, w)| (-w, d)).min();
+                let widest = scores.iter().map(|&(_, w)| w).max();
+
+                let found = |mode: WidthMode| {
+                    mode.find_path(&t, &capacity, demand, source, destination)
+                        .map(|path| score(&path))
+                };
+                assert_eq!(
+                    found(WidthMode::WidestShortest).map(|(d, w)| (d, -w)),
+                    widest_shortest
+                );
+                assert_eq!(
+                    found(WidthMode::ShortestWidest).map(|(d, w)| (-w, d)),
+                    shortest_widest
+                );
+                assert_eq!(found(WidthMode::Widest).map(|(_, w)| w), widest);
+            }
+        }
+    }
+}
+
+#[test]
+fn precomputed_trees_match_per_pair_search() {
+    let mut rng = ChaCha8Rng::seed_from_u64(5);
+    for _ in 0..50 {
+        let node_num = rng.gen_range(3..15);
+        let mut links = Vec::new();
+        for node1 in 0..node_num {
+            for node2 in node1 + 1..node_num {
+                if rng.gen_bool(0.3) {
+                    links.push(Link {
+                        node1,
+                        node2,
+                        distance: rng.gen_range(0..4),
+                        capacity: rng.gen_range(1..6),
+                    });
+                }
+            }
+        }
+        let t = Topology::new(node_num, links);
+        let capacity = t.capacities();
+        for width in [
+            WidthMode::WidestShortest,
+            WidthMode::ShortestWidest,
+            WidthMode::Widest,
+        ] {
+            let options = router::Options {
+                width,
+                ..router::Options::default()
+            };
+            let mut router = router::build_with("maximum-shortest-path", &t, &options).unwrap();
+            for source in 0..node_num {
+                for destination in 0..node_num {
+                    if source != destination {
+                        assert_eq!(
+                            router.find_path(source, destination, &capacity, 1),
+                            width.find_path(&t, &capacity, 1, source, destination)
+                        );
+                    }
+                }
+            }
+        }
+    }
+}